@@ -5,7 +5,10 @@ use proc_macro::{TokenStream, TokenTree, Literal};
 pub fn dash_conversion(input: TokenStream) -> TokenStream {
     match input.into_iter().next() {
         Some(TokenTree::Ident(name)) => {
-            Into::<TokenTree>::into(Literal::string(&name.to_string().replace("_", "-"))).into()
+            // raw identifiers (eg. `r#move`) are named without the prefix
+            let name = name.to_string();
+            let name = name.strip_prefix("r#").unwrap_or(&name);
+            Into::<TokenTree>::into(Literal::string(&name.replace("_", "-"))).into()
         }
         got => panic!("expected ident; got: {got:?}"),
     }
@@ -2,6 +2,8 @@ use crate::{
     app::{App, AppState},
     args::Args,
     commands::{cmd, Action},
+    pathlist,
+    tree::Tree,
 };
 use crossterm::{
     event::{self, Event as IOEvent}, //::{self, DisableMouseCapture, EnableMouseCapture},
@@ -9,7 +11,6 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use dirs::home_dir;
-use dunce;
 use notify::{recommended_watcher, Event as FSEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use std::{
     env::{current_dir, set_current_dir},
@@ -18,6 +19,7 @@ use std::{
     path::{Component, Path, PathBuf},
     sync::{Arc, Condvar, Mutex},
     thread,
+    time::Duration,
 };
 use tui::{backend::CrosstermBackend, terminal::Terminal};
 
@@ -63,6 +65,23 @@ impl<W: io::Write> Drop for TerminalWrap<W> {
     }
 }
 
+/// once the list of paths was read from it, stdin is replaced with the
+/// terminal, for keys to be read (and for the commands that are run)
+#[cfg(unix)]
+fn stdin_from_tty() -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let tty = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")?;
+    // SAFETY: both are open descriptors (`tty` until the end of the
+    // function, the duplicate stays open after it is closed)
+    if unsafe { libc::dup2(tty.as_raw_fd(), libc::STDIN_FILENO) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 enum ExternalEvent {
     None,
@@ -73,7 +92,7 @@ enum ExternalEvent {
 pub struct AllTheStuff {
     app: App,
     terminal: TerminalWrap<io::Stderr>, // moved into app (possible?)
-    watcher: RecommendedWatcher,        // moved into app
+    _watcher: RecommendedWatcher,       // moved into app
     ex_event: Arc<(Mutex<ExternalEvent>, Condvar)>, // moved into app
 }
 
@@ -99,20 +118,62 @@ impl AllTheStuff {
         acc
     }
 
-    pub fn new(args: Args) -> Result<Self, Box<dyn Error>> {
+    /// the app as it is once the config was run, and before
+    /// anything is drawn
+    pub fn load_app(args: Args) -> Result<App, Box<dyn Error>> {
         let cwd = current_dir()?;
 
-        let dir = dunce::canonicalize({
+        let target = dunce::canonicalize({
             let r = args.path.unwrap_or(cwd.clone());
             if r.is_absolute() { r } else { cwd.join(r) }
         })?;
 
+        // a tree of only the listed paths, rooted where they all are
+        let listed = if args.from_stdin || args.from_file.is_some() {
+            let cwd = dunce::canonicalize(&cwd)?;
+            let paths = match &args.from_file {
+                Some(file) => pathlist::read(io::BufReader::new(fs::File::open(file)?), &cwd)?,
+                None => {
+                    let paths = pathlist::read(io::stdin().lock(), &cwd)?;
+                    // (no keys are read when only printing)
+                    #[cfg(unix)]
+                    if !args.print {
+                        stdin_from_tty()?;
+                    }
+                    paths
+                }
+            };
+            Some((pathlist::root(&paths, &cwd), paths))
+        } else {
+            None
+        };
+
+        // when given something else than a directory, root at the
+        // current directory (or its parent if not under it) and
+        // reveal it once everything is setup
+        let (dir, reveal) = if let Some((root, _)) = &listed {
+            (root.clone(), None)
+        } else if target.is_dir() || args.doc {
+            (target, None)
+        } else {
+            let cwd = dunce::canonicalize(&cwd)?;
+            let root = if target.starts_with(&cwd) {
+                cwd
+            } else {
+                target.parent().unwrap().to_path_buf()
+            };
+            let rel = target.strip_prefix(&root).unwrap().to_path_buf();
+            (root, Some(rel))
+        };
+
         if args.changedir {
-            set_current_dir(&dir)?;
+            set_current_dir(if dir.is_dir() { &dir } else { dir.parent().unwrap() })?;
         }
 
         let mut app = {
-            if args.clearstate {
+            if let Some((_, paths)) = listed {
+                App::from_tree(Tree::from_paths(dir, paths))?
+            } else if args.clearstate {
                 App::new(dir)?
             } else if let Ok(content) = fs::read_to_string(Self::get_save_path(&dir)) {
                 if let Ok(mut r) = serde_json::from_str::<App>(&content) {
@@ -135,6 +196,14 @@ impl AllTheStuff {
             }
         }
 
+        if let Some(rel) = reveal {
+            app.reveal(&rel);
+        }
+
+        Ok(app)
+    } // fn load_app
+
+    pub fn new(mut app: App) -> Result<Self, Box<dyn Error>> {
         let mut terminal = TerminalWrap::new(io::stderr())?;
         // draw once as soon as possible
         terminal.0.draw(|f| app.draw(f))?;
@@ -168,7 +237,7 @@ impl AllTheStuff {
         Ok(Self {
             app,
             terminal,
-            watcher,
+            _watcher: watcher,
             ex_event,
         })
     } // fn new
@@ -184,6 +253,7 @@ impl AllTheStuff {
                 ExternalEvent::FSEvent(_fs_ev) => Action::Fn(&cmd::reload).apply(self.app, &[]),
             };
             *event = ExternalEvent::None;
+            self.app.job_poll();
 
             match self.app.state {
                 AppState::None => (),
@@ -209,7 +279,12 @@ impl AllTheStuff {
             }
 
             self.terminal.0.draw(|f| self.app.draw(f))?;
-            event = cvar.wait(event).unwrap();
+            // keep redrawing while something runs in the background
+            event = if self.app.busy() {
+                cvar.wait_timeout(event, Duration::from_millis(100)).unwrap().0
+            } else {
+                cvar.wait(event).unwrap()
+            };
         }
 
         Ok(())
@@ -1,13 +1,24 @@
 use crate::{
     commands::{Action, CommandMap, Key},
+    fileop::{Job, Operation},
+    fuzzy::{Fuzzy, FuzzyOutcome},
+    info::Popup,
+    journal::{Journal, Record},
     line::{Line, Message, Status},
-    node::Movement,
+    node::{MetaFormat, Movement, Renewed},
+    preview::Preview,
+    source::TreeSource,
     tree::Tree,
     view::View,
 };
 use crossterm::event::{Event, KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, io, iter, path::PathBuf};
+use std::{
+    collections::HashMap,
+    io, iter,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -20,6 +31,69 @@ use tui::{
 enum ViewTree {
     Leaf(View),
     Split(Vec<ViewTree>, u8), // XXX: tui::layout::Direction not serializable?
+    /// where the preview goes (never focused, at most one)
+    Preview,
+}
+
+impl ViewTree {
+    fn has_preview(&self) -> bool {
+        match self {
+            ViewTree::Leaf(_) => false,
+            ViewTree::Split(chs, _) => chs.iter().any(ViewTree::has_preview),
+            ViewTree::Preview => true,
+        }
+    }
+
+    fn count_views(&self) -> usize {
+        match self {
+            ViewTree::Leaf(_) => 1,
+            ViewTree::Split(chs, _) => chs.iter().map(ViewTree::count_views).sum(),
+            ViewTree::Preview => 0,
+        }
+    }
+
+    /// the path to the first view
+    fn first_view(&self) -> Option<Vec<usize>> {
+        match self {
+            ViewTree::Leaf(_) => Some(Vec::new()),
+            ViewTree::Split(chs, _) => chs.iter().enumerate().find_map(|(idx, ch)| {
+                ch.first_view().map(|mut path| {
+                    path.insert(0, idx);
+                    path
+                })
+            }),
+            ViewTree::Preview => None,
+        }
+    }
+
+    // `None` if it was the preview itself; else without the preview
+    // and where `focus` (if within) now is
+    fn without_preview(self, focus: Option<&[usize]>) -> Option<(ViewTree, Vec<usize>)> {
+        let ViewTree::Split(chs, d) = self else {
+            return match self {
+                ViewTree::Preview => None,
+                _ => Some((self, Vec::new())),
+            };
+        };
+        let mut kept = Vec::with_capacity(chs.len());
+        let mut new_focus = Vec::new();
+        for (idx, ch) in chs.into_iter().enumerate() {
+            let within = focus.and_then(|f| f.split_first()).filter(|(first, _)| idx == **first);
+            if let Some((ch, rest)) = ch.without_preview(within.map(|(_, rest)| rest)) {
+                if within.is_some() {
+                    new_focus = [&[kept.len()], rest.as_slice()].concat();
+                }
+                kept.push(ch);
+            }
+        }
+        if 1 == kept.len() {
+            // (a split of one is just that one)
+            let rest = new_focus.split_off(new_focus.len().min(1));
+            Some((kept.pop().unwrap(), rest))
+        } else {
+            Some((ViewTree::Split(kept, d), new_focus))
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -29,6 +103,8 @@ struct Internal {
     focus: Vec<usize>,
 
     variables: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    meta_format: MetaFormat,
 }
 
 impl Internal {
@@ -97,15 +173,21 @@ impl Internal {
     }
 
     pub fn view_split(&mut self, d: Direction) {
+        let niw = ViewTree::Leaf(self.focused().clone());
+        self.split_with(d, niw, true);
+    }
+
+    // `niw` goes after the focused view, `to_new` to focus it
+    fn split_with(&mut self, d: Direction, niw: ViewTree, to_new: bool) {
         let d = match d {
             Direction::Horizontal => 0,
             Direction::Vertical => 1,
         };
+        let step = usize::from(to_new);
 
         if self.focus.is_empty() {
-            let niw = self.views.clone();
             self.views = ViewTree::Split(vec![self.views.clone(), niw], d);
-            self.focus.push(1);
+            self.focus.push(step);
             return;
         }
 
@@ -113,26 +195,48 @@ impl Internal {
         let last = self.focus[len - 1];
 
         let Some(ViewTree::Split(chs, already)) = self.focused_group_mut() else { return; };
-        let ViewTree::Leaf(cur) = &chs[last] else { return; };
-        let niw = ViewTree::Leaf(cur.clone());
 
         if d == *already {
             // adding a split within one of same direction:
-            // insert it after current and make it current
+            // insert it after current (and make it current)
             chs.insert(last + 1, niw);
-            self.focus[len - 1] += 1;
+            self.focus[len - 1] += step;
         } else {
             // creating a split of different direction
             // in-place of the current one, that takes
             // the current one as first child
             // (ie. somewhat same as when focus is empty)
             chs[last] = ViewTree::Split(vec![chs[last].clone(), niw], d);
-            self.focus.push(1);
+            self.focus.push(step);
         }
 
         // self
     }
 
+    /// add the preview next to the focused view, or remove it
+    pub fn preview_toggle(&mut self) {
+        if !self.views.has_preview() {
+            self.split_with(Direction::Vertical, ViewTree::Preview, false);
+            return;
+        }
+        let views = std::mem::replace(&mut self.views, ViewTree::Preview);
+        // (the focus is never on the preview, so it is kept)
+        let (views, focus) = views.without_preview(Some(&self.focus)).unwrap();
+        self.views = views;
+        self.focus = focus;
+    }
+
+    // if the focus is not on a view (eg. after a close)
+    fn refocus(&mut self) {
+        let at = self.focus.iter().try_fold(&self.views, |acc, idx| match acc {
+            ViewTree::Split(chs, _) => chs.get(*idx),
+            _ => None,
+        });
+        if !matches!(at, Some(ViewTree::Leaf(_))) {
+            self.focus = self.views.first_view().unwrap_or_default();
+        }
+    }
+
     pub fn view_transpose(&mut self) {
         let gr = self.focused_group_mut();
         if let Some(ViewTree::Split(_, d)) = gr {
@@ -142,7 +246,8 @@ impl Internal {
 
     // FIXME: `wvwswhwq`
     pub fn view_close(&mut self) {
-        if self.focus.is_empty() {
+        // (the preview does not count, it does not stay alone)
+        if self.focus.is_empty() || self.views.count_views() < 2 {
             return;
         }
         let len = self.focus.len();
@@ -168,6 +273,7 @@ impl Internal {
                 }
             }
         } //else // YYY: quit on last view close? I prefer no
+        self.refocus();
     }
 
     pub fn view_close_other(&mut self) {
@@ -178,22 +284,30 @@ impl Internal {
     pub fn focus_to_view_adjacent(&mut self, movement: Movement) {
         let Some(ViewTree::Split(v, _)) = self.focused_group() else { return; };
         let max = v.len();
+        let previews = v
+            .iter()
+            .map(|it| matches!(it, ViewTree::Preview))
+            .collect::<Vec<_>>();
         if !self.focus.is_empty() {
             if let Some(it) = self.focus.last_mut() {
-                match movement {
-                    Movement::Forward => {
-                        *it += 1;
-                        if max <= *it {
-                            *it = 0;
+                // (the preview is skipped over)
+                while {
+                    match movement {
+                        Movement::Forward => {
+                            *it += 1;
+                            if max <= *it {
+                                *it = 0;
+                            }
                         }
-                    }
-                    Movement::Backward => {
-                        if 0 == *it {
-                            *it = max;
+                        Movement::Backward => {
+                            if 0 == *it {
+                                *it = max;
+                            }
+                            *it -= 1
                         }
-                        *it -= 1
                     }
-                }
+                    previews[*it]
+                } {}
             }
         }
     }
@@ -225,7 +339,13 @@ impl Internal {
             match stack.last() {
                 Some(ViewTree::Split(v, already)) if d == *already => {
                     let now_focus_len = stack.len();
-                    let now_focus_last = self.focus[now_focus_len - 1] as i32 + movement as i32;
+                    let is_preview = |at: i32| {
+                        0 <= at && v.get(at as usize).is_some_and(|it| matches!(it, ViewTree::Preview))
+                    };
+                    let mut now_focus_last = self.focus[now_focus_len - 1] as i32 + movement as i32;
+                    while is_preview(now_focus_last) {
+                        now_focus_last += movement as i32;
+                    }
 
                     let fitting = 0 <= now_focus_last && now_focus_last < v.len() as i32;
                     if fitting {
@@ -237,17 +357,22 @@ impl Internal {
                         let mut a = &v[now_focus_last];
                         while let ViewTree::Split(v, dd) = a {
                             // not even sure this is possible
-                            a = &v[if d == *dd {
-                                let border = match movement {
+                            let at = if d == *dd {
+                                match movement {
                                     Movement::Forward => v.len() - 1,
                                     Movement::Backward => 0,
-                                };
-                                self.focus.push(border);
-                                border
+                                }
                             } else {
-                                self.focus.push(0);
                                 0
-                            }];
+                            };
+                            // (next to the preview if it is there)
+                            let at = match (&v[at], at) {
+                                (ViewTree::Preview, 0) => 1,
+                                (ViewTree::Preview, at) => at - 1,
+                                _ => at,
+                            };
+                            self.focus.push(at);
+                            a = &v[at];
                         }
 
                         return;
@@ -273,17 +398,33 @@ impl Internal {
             // same as "{.}", ie at cursor full path
             let (view, tree) = self.focused_and_tree();
             let (_, node) = view.at_cursor_pair(tree);
-            return vec![node.as_path().to_string_lossy().to_string()];
+            return vec![node.location()];
         }
 
         match name.split_once('.').unwrap_or((name, "")) {
-            (obj @ ("" | "root" | "selection"), ppt) => {
+            (obj, ppt)
+                if matches!(obj, "" | "root" | "selection") || obj.starts_with("selection:") =>
+            {
                 let (view, tree) = self.focused_and_tree();
 
-                let nodes = match obj {
-                    "" => vec![view.at_cursor_pair(tree).1],
-                    "root" => vec![&tree.root],
-                    "selection" => view
+                // named selections are not necessarily loaded in the tree
+                let named = obj.strip_prefix("selection:").map(|sel| {
+                    view.get_selection(sel)
+                        .map(|paths| {
+                            paths
+                                .iter()
+                                .map(|rel| tree.root.as_path().join(rel))
+                                .filter_map(|path| tree.root.new_sibling(path).ok())
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default()
+                });
+
+                let nodes = match (obj, &named) {
+                    (_, Some(named)) => named.iter().collect(),
+                    ("", _) => vec![view.at_cursor_pair(tree).1],
+                    ("root", _) => vec![&tree.root],
+                    ("selection", _) => view
                         .collect_marked_pair(tree)
                         .into_iter()
                         .map(|(_, node)| node)
@@ -293,10 +434,7 @@ impl Internal {
                 };
 
                 match ppt {
-                    "" => nodes
-                        .iter()
-                        .map(|it| it.as_path().to_string_lossy().to_string())
-                        .collect(),
+                    "" => nodes.iter().map(|it| it.location()).collect(),
                     "relative" => nodes
                         .iter()
                         .map(|it| {
@@ -312,10 +450,38 @@ impl Internal {
                         .iter()
                         .map(|it| it.extension().unwrap_or("").to_string())
                         .collect(),
+                    "size" => nodes
+                        .iter()
+                        .map(|it| it.size_to_string(&self.meta_format))
+                        .collect(),
+                    "atime" => nodes
+                        .iter()
+                        .map(|it| it.time_to_string(|m| m.accessed, &self.meta_format))
+                        .collect(),
+                    "mtime" => nodes
+                        .iter()
+                        .map(|it| it.time_to_string(|m| m.modified, &self.meta_format))
+                        .collect(),
+                    "ctime" => nodes
+                        .iter()
+                        .map(|it| it.time_to_string(|m| m.created, &self.meta_format))
+                        .collect(),
+                    "mime" => nodes.iter().map(|it| it.magic().mime.to_string()).collect(),
+                    "type" => nodes.iter().map(|it| it.magic().kind.to_string()).collect(),
+                    "pointer" => nodes
+                        .iter()
+                        .map(|it| it.member().map_or(String::new(), |m| m.pointer.clone()))
+                        .collect(),
                     _ => panic!("unknown property on object: '{obj}.{ppt}'"),
                 }
             }
 
+            ("selections", "") => {
+                let mut names = self.focused().list_selections().keys().cloned().collect::<Vec<_>>();
+                names.sort_unstable();
+                names
+            }
+
             (obj, "") => {
                 if let Some(it) = self.variables.get(obj) {
                     it.clone()
@@ -340,6 +506,7 @@ pub enum AppState {
     Quit,
     Pause,
     Pending(Box<dyn FnOnce(App) -> App>),
+    #[allow(dead_code)] // name of the file being sourced
     Sourcing(String),
 }
 
@@ -351,6 +518,16 @@ pub struct App {
     bindings: CommandMap,
     #[serde(skip_serializing, skip_deserializing)]
     status: Status,
+    #[serde(skip_serializing, skip_deserializing)]
+    fuzzy: Option<Fuzzy>,
+    #[serde(skip_serializing, skip_deserializing)]
+    info: Option<Popup>,
+    #[serde(skip_serializing, skip_deserializing)]
+    job: Option<Job>,
+    #[serde(skip_serializing, skip_deserializing)]
+    journal: Journal,
+    #[serde(skip_serializing, skip_deserializing)]
+    preview: Option<Preview>,
 
     #[serde(skip_serializing, skip_deserializing)]
     pub state: AppState,
@@ -367,6 +544,8 @@ pub struct App {
 fn draw_r<B: Backend>(
     view_node: &mut ViewTree,
     tree: &Tree,
+    preview: &mut Option<Preview>,
+    at_cursor: &(Rc<dyn TreeSource>, PathBuf),
     f: &mut Frame<'_, B>,
     area: Rect,
     focus_path: Option<&[usize]>,
@@ -388,6 +567,13 @@ fn draw_r<B: Backend>(
             f.render_stateful_widget(tree, surround.inner(area), view);
         }
 
+        ViewTree::Preview => {
+            let preview = preview.get_or_insert_with(Preview::default);
+            let (source, path) = at_cursor;
+            preview.update(source, path, area.height.saturating_sub(2) as usize);
+            f.render_widget(&*preview, area);
+        }
+
         ViewTree::Split(children, dir) => {
             if next_focus {
                 let surround = Block::default()
@@ -412,6 +598,8 @@ fn draw_r<B: Backend>(
                 draw_r(
                     child,
                     tree,
+                    preview,
+                    at_cursor,
                     f,
                     chunk,
                     focus_path.and_then(|p_slice| {
@@ -433,7 +621,10 @@ fn draw_r<B: Backend>(
 impl App {
     /// rem: the path should be canonical!
     pub fn new(path: PathBuf) -> io::Result<App> {
-        let mut tree = Tree::new(path)?;
+        App::from_tree(Tree::new(path)?)
+    }
+
+    pub fn from_tree(mut tree: Tree) -> io::Result<App> {
         let mut view = View::new(&tree.root)?;
         view.unfold_root(&mut tree)?;
         Ok(App {
@@ -443,28 +634,84 @@ impl App {
                 focus: Vec::new(),
 
                 variables: HashMap::new(),
+                meta_format: MetaFormat::default(),
             },
 
             bindings: CommandMap::default(),
             status: Status::default(),
+            fuzzy: None,
+            info: None,
+            job: None,
+            journal: Journal::new(),
+            preview: None,
             state: AppState::None,
         })
     }
 
-    fn fixup_r(vt: &mut ViewTree, ptree: &Tree, tree: &Tree) {
+    fn fixup_r(vt: &mut ViewTree, ptree: &Tree, tree: &mut Tree) {
         match vt {
-            ViewTree::Leaf(view) => view.fixup(ptree, tree),
+            ViewTree::Leaf(view) => {
+                view.fixup(ptree, tree);
+                view.refresh_flat(tree);
+            }
             ViewTree::Split(list, _) => {
                 for it in list {
                     App::fixup_r(it, ptree, tree);
                 }
             }
+            ViewTree::Preview => (),
         }
     }
+
+    fn fixup_within_r(vt: &mut ViewTree, tree: &mut Tree, renewed: &[Renewed]) {
+        match vt {
+            ViewTree::Leaf(view) => {
+                view.fixup_within(tree, renewed);
+                view.refresh_flat(tree);
+            }
+            ViewTree::Split(list, _) => {
+                for it in list {
+                    App::fixup_within_r(it, tree, renewed);
+                }
+            }
+            ViewTree::Preview => (),
+        }
+    }
+
+    fn reveal_r(vt: &mut ViewTree, tree: &mut Tree, path: &Path) -> io::Result<bool> {
+        match vt {
+            ViewTree::Leaf(view) => view.reveal(tree, path),
+            ViewTree::Split(list, _) => list.iter_mut().try_fold(false, |acc, it| {
+                App::reveal_r(it, tree, path).map(|found| acc || found)
+            }),
+            ViewTree::Preview => Ok(false),
+        }
+    }
+
     pub fn fixup(&mut self) {
-        let new = self.i.tree.renew().unwrap();
-        App::fixup_r(&mut self.i.views, &self.i.tree, &new);
-        self.i.tree = new;
+        match self.i.tree.renew() {
+            Ok(mut new) => {
+                App::fixup_r(&mut self.i.views, &self.i.tree, &mut new);
+                self.i.tree = new;
+                if let Some(preview) = &mut self.preview {
+                    preview.invalidate();
+                }
+            }
+            Err(err) => self.message(Message::Error(format!("could not update the tree: {err}"))),
+        }
+    }
+
+    /// update the tree (and views) only where `paths` are
+    pub fn fixup_within(&mut self, paths: &[PathBuf]) {
+        match self.i.tree.renew_within(paths) {
+            Ok(renewed) => {
+                App::fixup_within_r(&mut self.i.views, &mut self.i.tree, &renewed);
+                if let Some(preview) = &mut self.preview {
+                    preview.invalidate();
+                }
+            }
+            Err(err) => self.message(Message::Error(format!("could not update the tree: {err}"))),
+        }
     }
 
     pub fn rebind(&mut self, key_path: &[Key], action: Action) {
@@ -482,11 +729,15 @@ impl App {
             .split(f.size());
         let (main, line) = (main0_line1[0], main0_line1[1]);
 
+        let (_, node) = self.i.focused().at_cursor_pair(&self.i.tree);
+        let at_cursor = (Rc::clone(node.source()), node.as_path().to_path_buf());
         match &mut self.i.views {
             ViewTree::Leaf(view) => f.render_stateful_widget(&self.i.tree, main, view),
-            ViewTree::Split(_, _) => draw_r(
+            _ => draw_r(
                 &mut self.i.views,
                 &self.i.tree,
+                &mut self.preview,
+                &at_cursor,
                 f,
                 main,
                 Some(&self.i.focus),
@@ -494,7 +745,11 @@ impl App {
         }
 
         let (view, tree) = self.i.focused_and_tree();
-        f.render_stateful_widget(Line::new(view, tree), line, &mut self.status);
+        f.render_stateful_widget(
+            Line::new(view, tree, &self.i.meta_format),
+            line,
+            &mut self.status,
+        );
 
         if let Some(tb) = self.status.long_message() {
             let h = tb.height();
@@ -507,7 +762,16 @@ impl App {
             f.render_widget(tb, r);
         }
 
-        if let Some(s) = self.status.cursor_shift() {
+        if let Some(popup) = &self.info {
+            f.render_widget(popup, popup.area(main));
+        }
+
+        if let Some(fz) = &mut self.fuzzy {
+            fz.update();
+            let r = fz.area(main);
+            f.render_widget(&*fz, r);
+            f.set_cursor(r.x + fz.cursor_shift(), r.y + r.height - 2);
+        } else if let Some(s) = self.status.cursor_shift() {
             f.set_cursor(line.x + s, line.y);
         }
     }
@@ -520,6 +784,24 @@ impl App {
         self.status.prompt(prompt, action, initial);
     }
 
+    pub fn prompt_cursor(&mut self, at: usize) {
+        self.status.prompt_cursor(at);
+    }
+
+    /// apply the action, recording what it changed of the focused
+    /// view (if anything) in its undo history
+    fn apply_tracked(mut self, action: &Action, args: &[&str]) -> App {
+        let focus = self.i.focus.clone();
+        let (view, tree) = self.focused_and_tree_mut();
+        view.history_checkpoint(tree);
+        let mut app = action.apply(self, args);
+        if app.i.focus == focus {
+            let (view, tree) = app.focused_and_tree_mut();
+            view.history_commit(tree);
+        }
+        app
+    }
+
     pub fn do_event(mut self, event: &Event) -> App {
         // ZZZ: hard-coded for now
         if let Event::Key(key) = event {
@@ -538,10 +820,32 @@ impl App {
             }
         }
 
+        // any key closes the info popup
+        if let (Some(_), Event::Key(_)) = (&self.info, event) {
+            self.info = None;
+            return self;
+        }
+
+        if let Some(fz) = &mut self.fuzzy {
+            match fz.do_event(event) {
+                FuzzyOutcome::Continue => (),
+                FuzzyOutcome::Cancel => self.fuzzy = None,
+                FuzzyOutcome::Accept(path) => {
+                    self.fuzzy = None;
+                    let (view, tree) = self.focused_and_tree_mut();
+                    view.history_checkpoint(tree);
+                    self.reveal(&path);
+                    let (view, tree) = self.focused_and_tree_mut();
+                    view.history_commit(tree);
+                }
+            }
+            return self;
+        }
+
         let (prompt_action, event_consumed) =
             self.status.do_event(event, &|name| self.i.lookup(name));
         if let Some((action, args)) = prompt_action {
-            return action.apply(self, &args.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+            return self.apply_tracked(&action, &args.iter().map(|s| s.as_str()).collect::<Vec<_>>());
         }
         if event_consumed {
             return self;
@@ -550,6 +854,7 @@ impl App {
         if let Event::Key(key) = event {
             if let KeyCode::Esc = key.code {
                 self.status.clear_pending();
+                self.focused_mut().visual_cancel();
             } else {
                 self.status.push_pending(key.into());
                 let crap = self.bindings.clone(); // XXX: this should not be needed
@@ -557,7 +862,7 @@ impl App {
 
                 if let Some(action) = may {
                     self.status.clear_pending();
-                    return action.apply(self, &[]);
+                    return self.apply_tracked(action, &[]);
                 }
                 if !continues {
                     self.status.clear_pending();
@@ -579,7 +884,9 @@ impl App {
         self.i.focused_mut()
     }
 
-    //pub fn focused_and_tree(&self) -> (&View, &Tree) { self.i.focused_and_tree() }
+    pub fn focused_and_tree(&self) -> (&View, &Tree) {
+        self.i.focused_and_tree()
+    }
 
     pub fn focused_and_tree_mut(&mut self) -> (&mut View, &mut Tree) {
         self.i.focused_and_tree_mut()
@@ -620,4 +927,108 @@ impl App {
     pub fn var_list(&self) -> Vec<&String> {
         self.i.var_list()
     }
+
+    pub fn info_open(&mut self, popup: Popup) {
+        self.info = Some(popup);
+    }
+
+    pub fn fuzzy_open(&mut self) {
+        let (view, tree) = self.i.focused_and_tree();
+        self.fuzzy = Some(Fuzzy::new(
+            tree.root.as_path(),
+            view.list_filtering().clone(),
+        ));
+    }
+
+    /// `path` is relative to the root
+    pub fn reveal(&mut self, path: &Path) {
+        let (view, tree) = self.focused_and_tree_mut();
+        match view.reveal(tree, path) {
+            Ok(true) => (),
+            Ok(false) => self.message(Message::Warning(format!(
+                "could not reveal '{}' (not found or filtered out)",
+                path.to_string_lossy()
+            ))),
+            Err(err) => self.message(Message::Error(format!(
+                "could not reveal '{}': {err}",
+                path.to_string_lossy()
+            ))),
+        }
+    }
+
+    /// same as `reveal` but in every views
+    pub fn reveal_everywhere(&mut self, path: &Path) {
+        match App::reveal_r(&mut self.i.views, &mut self.i.tree, path) {
+            Ok(true) => (),
+            Ok(false) => self.message(Message::Warning(format!(
+                "could not reveal '{}' (not found or filtered out)",
+                path.to_string_lossy()
+            ))),
+            Err(err) => self.message(Message::Error(format!(
+                "could not reveal '{}': {err}",
+                path.to_string_lossy()
+            ))),
+        }
+    }
+
+    pub fn busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    pub fn job_start(&mut self, job: Job) {
+        self.message(Message::Info(job.status()));
+        self.job = Some(job);
+    }
+
+    /// show the progress of the running job, if any, or its
+    /// outcome when it is done
+    pub fn job_poll(&mut self) {
+        let Some(job) = &self.job else { return; };
+        let Some(report) = job.report() else {
+            let status = job.status();
+            self.message(Message::Info(status));
+            return;
+        };
+        self.job = None;
+        let record = match report.op {
+            Operation::Copy => Record::Copy(report.pairs),
+            Operation::Move => Record::Move(report.pairs),
+        };
+        self.fixup_within(&record.paths());
+        // what was overwritten comes back with the second undo
+        self.journal.record(Record::Trash(report.trashed));
+        self.journal.record(record);
+        self.message(if report.errors.is_empty() {
+            Message::Info(report.summary)
+        } else {
+            Message::Error(format!("{}, but:\n{}", report.summary, report.errors.join("\n")))
+        });
+    }
+
+    pub fn journal_mut(&mut self) -> &mut Journal {
+        &mut self.journal
+    }
+
+    pub fn preview_toggle(&mut self) {
+        self.i.preview_toggle();
+        self.preview = None;
+    }
+
+    /// @ret false if there is no preview
+    pub fn preview_scroll(&mut self, by: i32) -> bool {
+        if !self.i.views.has_preview() {
+            return false;
+        }
+        let preview = self.preview.get_or_insert_with(Preview::default);
+        preview.scroll(by);
+        true
+    }
+
+    pub fn meta_format(&self) -> &MetaFormat {
+        &self.i.meta_format
+    }
+
+    pub fn meta_format_mut(&mut self) -> &mut MetaFormat {
+        &mut self.i.meta_format
+    }
 }
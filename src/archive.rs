@@ -0,0 +1,315 @@
+// archives (.zip, .tar, .tar.gz, .tar.zst) listed and extracted
+// without being unpacked, to be browsed as read-only directories
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+/// what the archive says of one of its entries
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct Header {
+    pub size: u64,
+    /// seconds since epoch
+    pub mtime: i64,
+    pub mode: u32,
+    pub dir: bool,
+    /// a symbolic or hard link (these are not extracted)
+    #[serde(default)]
+    pub link: bool,
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    /// relative to the archive, without any `.` or `..`
+    pub path: PathBuf,
+    pub header: Header,
+}
+
+fn format_of(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    [
+        (".zip", Format::Zip),
+        (".tar", Format::Tar),
+        (".tar.gz", Format::TarGz),
+        (".tgz", Format::TarGz),
+        (".tar.zst", Format::TarZst),
+        (".tzst", Format::TarZst),
+    ]
+    .into_iter()
+    .find(|(ext, _)| name.ends_with(ext))
+    .map(|(_, format)| format)
+}
+
+/// from the name only, the content is not checked
+pub fn is_archive(path: &Path) -> bool {
+    format_of(path).is_some()
+}
+
+/// the archive among the ancestors of `path`, and the path
+/// of the entry within it
+pub fn split(path: &Path) -> Option<(&Path, &Path)> {
+    path.ancestors()
+        .find(|it| is_archive(it) && it.is_file())
+        .map(|archive| (archive, path.strip_prefix(archive).unwrap()))
+}
+
+/// `None` if it could escape the destination when extracted
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut r = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::Normal(name) => r.push(name),
+            Component::CurDir => (),
+            _ => return None,
+        }
+    }
+    (!r.as_os_str().is_empty()).then_some(r)
+}
+
+// days since epoch from (year, month, day), see
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if 0 <= y { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * if 2 < m { m - 3 } else { m + 9 } + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn zip_err(err: zip::result::ZipError) -> io::Error {
+    match err {
+        zip::result::ZipError::Io(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+fn tar_reader(path: &Path, format: Format) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match format {
+        Format::TarGz => Box::new(GzDecoder::new(file)),
+        Format::TarZst => Box::new(ruzstd::StreamingDecoder::new(file).map_err(io::Error::other)?),
+        _ => Box::new(file),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+/// go through the entries of the archive, with a reader on the
+/// content of each; stops on the first error from `f`
+fn for_each(
+    path: &Path,
+    mut f: impl FnMut(Entry, &mut dyn Read) -> io::Result<()>,
+) -> io::Result<()> {
+    let Some(format) = format_of(path) else {
+        return Err(io::Error::other("not a known archive format"));
+    };
+
+    if Format::Zip == format {
+        let mut zip = zip::ZipArchive::new(BufReader::new(File::open(path)?)).map_err(zip_err)?;
+        for k in 0..zip.len() {
+            let mut file = zip.by_index(k).map_err(zip_err)?;
+            let Some(path) = file.enclosed_name().and_then(normalize) else {
+                continue;
+            };
+            let at = file.last_modified();
+            let days = days_from_civil(at.year() as i64, at.month() as i64, at.day() as i64);
+            let header = Header {
+                size: file.size(),
+                mtime: days * 86400
+                    + at.hour() as i64 * 3600
+                    + at.minute() as i64 * 60
+                    + at.second() as i64,
+                mode: file.unix_mode().unwrap_or(0o644) & 0o7777,
+                dir: file.is_dir(),
+                link: file.unix_mode().is_some_and(|mode| 0o120000 == mode & 0o170000),
+            };
+            f(Entry { path, header }, &mut file)?;
+        }
+        return Ok(());
+    }
+
+    for ent in tar_reader(path, format)?.entries()? {
+        let mut ent = ent?;
+        let Some(path) = normalize(&ent.path()?) else {
+            continue;
+        };
+        let header = ent.header();
+        let header = Header {
+            size: header.size()?,
+            mtime: header.mtime()? as i64,
+            mode: header.mode()? & 0o7777,
+            dir: header.entry_type().is_dir(),
+            link: header.entry_type().is_symlink() || header.entry_type().is_hard_link(),
+        };
+        f(Entry { path, header }, &mut ent)?;
+    }
+    Ok(())
+}
+
+pub fn list(path: &Path) -> io::Result<Vec<Entry>> {
+    let mut r = Vec::new();
+    for_each(path, |ent, _| {
+        r.push(ent);
+        Ok(())
+    })?;
+    Ok(r)
+}
+
+/// extract the entry at `within` (with everything under it) into
+/// `dest`, or the whole archive if `within` is empty; existing files
+/// are not overwritten, @ret the paths created directly in `dest`
+/// and the links skipped (as entry paths)
+pub fn extract(
+    archive: &Path,
+    within: &Path,
+    dest: &Path,
+) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let within = if within.as_os_str().is_empty() {
+        PathBuf::new()
+    } else {
+        normalize(within).ok_or_else(|| io::Error::other("invalid entry path"))?
+    };
+    // an entry is extracted under its own name
+    let base = match within.file_name() {
+        Some(name) => dest.join(name),
+        None => dest.to_path_buf(),
+    };
+
+    let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    for_each(archive, |ent, content| {
+        let Ok(rel) = ent.path.strip_prefix(&within) else {
+            return Ok(());
+        };
+        if ent.header.link {
+            skipped.push(ent.path);
+            return Ok(());
+        }
+        // (joining "" would add a trailing separator)
+        let to = if rel.as_os_str().is_empty() {
+            base.clone()
+        } else {
+            base.join(rel)
+        };
+        let top = dest.join(to.strip_prefix(dest).unwrap().components().next().unwrap());
+        if !created.contains(&top) {
+            created.push(top);
+        }
+        if ent.header.dir {
+            return fs::create_dir_all(&to);
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&to)?;
+        io::copy(content, &mut file)?;
+        #[cfg(unix)]
+        if 0 != ent.header.mode {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(ent.header.mode & 0o777))?;
+        }
+        Ok(())
+    })?;
+
+    if created.is_empty() && skipped.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no such entry in the archive",
+        ));
+    }
+    Ok((created, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    // d/, d/f, d/l -> f, top and x/y/z (without its directories)
+    fn make_tar(path: &Path) {
+        let mut tar = tar::Builder::new(File::create(path).unwrap());
+        let mut add = |path: &str, kind: tar::EntryType, content: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_size(content.len() as u64);
+            header.set_mode(if kind.is_dir() { 0o755 } else { 0o640 });
+            header.set_mtime(1700000000);
+            if kind.is_symlink() {
+                header.set_link_name("f").unwrap();
+            }
+            tar.append_data(&mut header, path, content).unwrap();
+        };
+        add("d/", tar::EntryType::Directory, b"");
+        add("d/f", tar::EntryType::Regular, b"hi");
+        add("d/l", tar::EntryType::Symlink, b"");
+        add("top", tar::EntryType::Regular, b"top");
+        add("x/y/z", tar::EntryType::Regular, b"z");
+        tar.finish().unwrap();
+    }
+
+    #[test]
+    fn test_paths() {
+        assert!(is_archive(Path::new("a/b.TAR.GZ")));
+        assert!(is_archive(Path::new("b.tzst")));
+        assert!(!is_archive(Path::new("b.gz")));
+        assert_eq!(normalize(Path::new("./a/b")), Some(PathBuf::from("a/b")));
+        assert_eq!(normalize(Path::new("a/../../b")), None);
+        assert_eq!(normalize(Path::new("/etc/passwd")), None);
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 2, 29) * 86400, 951782400);
+    }
+
+    #[test]
+    fn test_list() {
+        let dir = TestDir::new("archive-list");
+        let path = dir.join("a.tar");
+        make_tar(&path);
+
+        let entries = list(&path).unwrap();
+        let paths = entries.iter().map(|it| it.path.to_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(paths, ["d", "d/f", "d/l", "top", "x/y/z"]);
+        let (d, f, l) = (entries[0].header, entries[1].header, entries[2].header);
+        assert!(d.dir && !d.link);
+        assert_eq!((f.size, f.mtime, f.mode, f.dir, f.link), (2, 1700000000, 0o640, false, false));
+        assert!(l.link);
+    }
+
+    #[test]
+    fn test_extract() {
+        let dir = TestDir::new("archive-extract");
+        let path = dir.join("a.tar");
+        make_tar(&path);
+
+        let dest = dir.join("all");
+        fs::create_dir(&dest).unwrap();
+        let (created, skipped) = extract(&path, Path::new(""), &dest).unwrap();
+        assert_eq!(created, ["d", "top", "x"].map(|it| dest.join(it)));
+        assert_eq!(skipped, [PathBuf::from("d/l")]);
+        assert_eq!(fs::read(dest.join("d/f")).unwrap(), b"hi");
+        assert_eq!(fs::read(dest.join("x/y/z")).unwrap(), b"z");
+        assert!(fs::symlink_metadata(dest.join("d/l")).is_err());
+        // not overwritten
+        assert!(extract(&path, Path::new("top"), &dest).is_err());
+
+        let dest = dir.join("some");
+        fs::create_dir(&dest).unwrap();
+        let (created, _) = extract(&path, Path::new("x/y"), &dest).unwrap();
+        assert_eq!(created, [dest.join("y")]);
+        assert_eq!(fs::read(dest.join("y/z")).unwrap(), b"z");
+        assert_eq!(
+            extract(&path, Path::new("nope"), &dest).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
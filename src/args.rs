@@ -4,13 +4,25 @@ use std::path::PathBuf;
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 pub struct Args {
-    /// path to open at, defaults to current directory
+    /// path to open at, or to reveal if not a directory, defaults to current directory
     pub path: Option<PathBuf>,
 
     /// move to the indicated directory first
     #[arg(short = 'C', default_value_t = false)]
     pub changedir: bool,
 
+    /// open the path as a JSON, TOML or YAML document, its values as the tree
+    #[arg(long, default_value_t = false)]
+    pub doc: bool,
+
+    /// show only the paths listed on stdin (one per line), keys are then read from the terminal
+    #[arg(long, default_value_t = false, conflicts_with = "from_file")]
+    pub from_stdin: bool,
+
+    /// show only the paths listed in the file (one per line)
+    #[arg(long)]
+    pub from_file: Option<PathBuf>,
+
     /// do not load any existing state for this path
     #[arg(short = 'x', long, default_value_t = false)]
     pub clearstate: bool,
@@ -22,4 +34,12 @@ pub struct Args {
     /// do not use any config
     #[arg(long, default_value_t = false)]
     pub clean: bool,
+
+    /// write the visible tree to stdout and exit, instead of being interactive
+    #[arg(long, default_value_t = false)]
+    pub print: bool,
+
+    /// with --print, draw the tree with ASCII characters only
+    #[arg(long, default_value_t = false, requires = "print")]
+    pub ascii: bool,
 }
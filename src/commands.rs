@@ -5,28 +5,251 @@
 /// (the actual action) with some meta (such as name/doc/completion).
 use crate::{
     app::{App, AppState},
+    archive,
     completions::Completer,
-    line::{split_line_args, Message},
-    node::{Filtering, Movement, Sorting, SortingProp},
+    fileop::{self, Conflict, Job, Operation},
+    info,
+    journal::Record,
+    line::{line_escape, split_line_args, Message},
+    node::{
+        mode_to_string, Filtering, Movement, Node, SizeFormat, Sorting, SortingProp, TimeFormat,
+    },
+    perms,
+    trash,
     view::ScanToChoice,
 };
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use dash_conversion::dash_conversion;
-use dunce;
 use dirs::home_dir;
 use glob::Pattern;
 use lazy_static::lazy_static;
+use regex::Regex;
 use std::{
     collections::HashMap,
     default::Default,
-    env::{current_dir, set_current_dir},
-    fmt, fs, io,
-    path::PathBuf,
+    env::{self, current_dir, set_current_dir},
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
     process::Command as SysCommand,
     str::FromStr,
+    time::SystemTime,
 };
 use tui::layout::Direction;
 
+fn mark_matching(mut app: App, recursive: bool, predicate: &impl Fn(&Node) -> bool) -> App {
+    let (view, tree) = app.focused_and_tree_mut();
+    let (count, errors) = view.mark_matching(tree, recursive, predicate);
+    let marked = format!("marked {} node{}", count, if count != 1 { "s" } else { "" });
+    app.message(if errors.is_empty() {
+        Message::Info(marked)
+    } else {
+        Message::Error(format!("{marked}, but:\n{}", errors.join("\n")))
+    });
+    app
+}
+
+/// an entry within an archive among `paths`, such nodes only exist
+/// in the archive and cannot be changed in place
+fn archive_entry(paths: &[PathBuf]) -> Option<&PathBuf> {
+    paths.iter().find(|it| {
+        archive::split(it).is_some_and(|(_, within)| !within.as_os_str().is_empty())
+    })
+}
+
+fn warn_archive_entry(app: &mut App, verb: &str, path: &Path) {
+    app.message(Message::Warning(format!(
+        "cannot {verb} '{}', it is within an archive (see extract-to)",
+        path.to_string_lossy()
+    )));
+}
+
+/// full paths of the marked nodes, or of the one at the cursor if none
+fn selection_or_cursor(app: &App) -> Vec<PathBuf> {
+    let selection = app.lookup("selection");
+    if selection.is_empty() {
+        app.lookup("")
+    } else {
+        selection
+    }
+    .into_iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+/// the node at the cursor if it is a directory, else its parent
+fn folder_at_cursor(app: &App) -> PathBuf {
+    let at_cursor = PathBuf::from(app.lookup("").remove(0));
+    if at_cursor.is_dir() {
+        at_cursor
+    } else {
+        at_cursor.parent().unwrap().to_path_buf()
+    }
+}
+
+/// create nodes with `create` for each of the `args` (relative to
+/// the folder at the cursor), then reveal the last one everywhere
+fn create_nodes(
+    mut app: App,
+    name: &str,
+    args: &[&str],
+    create: impl Fn(&Path) -> io::Result<()>,
+) -> App {
+    if args.is_empty() {
+        app.message(Message::Warning(format!("{name} needs at least a path")));
+        return app;
+    }
+
+    let root = PathBuf::from(app.lookup("root").remove(0));
+    let base = folder_at_cursor(&app);
+    let mut created = Vec::new();
+    let mut errors = Vec::new();
+    for it in args {
+        let path = base.join(it);
+        match create(&path) {
+            Ok(()) => created.push(path),
+            Err(err) => errors.push(format!("{}: {err}", path.to_string_lossy())),
+        }
+    }
+
+    // intermediate directories may have been created as well
+    let changed = created
+        .iter()
+        .map(|it| {
+            it.ancestors()
+                .take_while(|an| an.starts_with(&base) && *an != base)
+                .last()
+                .unwrap_or(it)
+                .to_path_buf()
+        })
+        .collect::<Vec<_>>();
+    app.fixup_within(&changed);
+
+    if let Some(rel) = created.last().and_then(|it| it.strip_prefix(&root).ok()) {
+        app.reveal_everywhere(rel);
+    }
+    if !errors.is_empty() {
+        app.message(Message::Error(errors.join("\n")));
+    }
+    app
+}
+
+/// create links to the marked nodes (or the one at the cursor), with
+/// the same names, in the given directory (or the folder at the cursor)
+fn link_nodes(
+    mut app: App,
+    name: &str,
+    args: &[&str],
+    link: fn(&Path, &Path) -> io::Result<()>,
+) -> App {
+    let root = PathBuf::from(app.lookup("root").remove(0));
+    let targets = selection_or_cursor(&app);
+    let dest = match args {
+        [] => folder_at_cursor(&app),
+        [dest] => root.join(dest),
+        _ => {
+            app.message(Message::Warning(format!("{name} takes at most a destination directory")));
+            return app;
+        }
+    };
+    // by default the links would be next to (or in) what they link to
+    if args.is_empty() && targets.iter().any(|it| *it == dest || it.parent() == Some(&dest)) {
+        app.message(Message::Warning(format!(
+            "{name} needs a destination directory (or mark the nodes and go to the directory)"
+        )));
+        return app;
+    }
+    let mut created = Vec::new();
+    let mut errors = Vec::new();
+    for it in &targets {
+        let Some(file_name) = it.file_name() else { continue; };
+        let path = dest.join(file_name);
+        match link(it, &path) {
+            Ok(()) => created.push(path),
+            Err(err) => errors.push(format!("{}: {err}", path.to_string_lossy())),
+        }
+    }
+
+    app.fixup_within(&created);
+    if let Some(rel) = created.last().and_then(|it| it.strip_prefix(&root).ok()) {
+        app.reveal_everywhere(rel);
+    }
+    if !errors.is_empty() {
+        app.message(Message::Error(errors.join("\n")));
+    }
+    app
+}
+
+fn file_operation(mut app: App, op: Operation, name: &str, args: &[&str]) -> App {
+    if app.busy() {
+        app.message(Message::Warning("an operation is already in progress".to_string()));
+        return app;
+    }
+
+    let mut conflict = Conflict::Ask;
+    let mut paths = Vec::new();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match *arg {
+            "-c" | "--on-conflict" => match it.next().map(|s| s.parse()) {
+                Some(Ok(c)) => conflict = c,
+                Some(Err(err)) => {
+                    app.message(Message::Warning(err));
+                    return app;
+                }
+                None => {
+                    app.message(Message::Warning(format!(
+                        "{name}: {arg} needs one of skip/overwrite/rename"
+                    )));
+                    return app;
+                }
+            },
+            _ => paths.push(arg),
+        }
+    }
+
+    // relative paths are from the root
+    let root = PathBuf::from(app.lookup("root").remove(0));
+    let mut paths = paths.into_iter().map(|it| root.join(it)).collect::<Vec<_>>();
+
+    let dest = paths.pop().unwrap_or_else(|| folder_at_cursor(&app));
+    if !dest.is_dir() {
+        app.message(Message::Warning(format!(
+            "{name}: '{}' is not a directory",
+            dest.to_string_lossy()
+        )));
+        return app;
+    }
+
+    let sources = if !paths.is_empty() {
+        paths
+    } else {
+        selection_or_cursor(&app)
+    };
+
+    if Conflict::Ask == conflict {
+        let taken = fileop::conflicts(&sources, &dest);
+        if !taken.is_empty() {
+            let what = match taken.as_slice() {
+                [one] => format!("'{}' already exists", one.file_name().unwrap().to_string_lossy()),
+                _ => format!("{} names already exist", taken.len()),
+            };
+            let mut bound = sources
+                .iter()
+                .map(|it| it.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            bound.push(dest.to_string_lossy().to_string());
+            bound.push("--on-conflict".to_string());
+            let then = if Operation::Copy == op { &cmd::copy } else { &cmd::r#move };
+            app.prompt(format!("{what} (skip/overwrite/rename): "), Action::Bind(then, bound), None);
+            return app;
+        }
+    }
+
+    app.job_start(Job::start(op, sources, dest, conflict));
+    app
+}
+
 #[derive(Debug, Clone)]
 pub enum Action {
     Fn(&'static StaticCommand),
@@ -127,6 +350,14 @@ const fn char_to_key_needs_shift(ch: char) -> bool {
     }
 }
 
+impl TryFrom<&str> for Key {
+    type Error = ();
+
+    fn try_from(s: &str) -> Result<Key, ()> {
+        s.parse()
+    }
+}
+
 // ZZZ: might be temporary
 impl From<char> for Key {
     fn from(ch: char) -> Key {
@@ -363,7 +594,7 @@ macro_rules! make_map_one {
 macro_rules! make_map {
     ($(($key:literal, $value:tt),)*) => {
         HashMap::from([
-            $(make_map_one!($key.into() => $value),)*
+            $(make_map_one!($key.try_into().expect("not a key") => $value),)*
         ])
     };
 }
@@ -395,6 +626,10 @@ impl Default for CommandMap {
             ),
             ('?', help),
             ('/', (prompt, "/", "find-in")),
+            ('F', fuzzy),
+            ('V', visual),
+            ('u', view_undo),
+            ("<C-r>", view_redo),
             ('n', find_in_next),
             ('N', find_in_prev),
             ('H', fold_node),
@@ -444,6 +679,8 @@ impl Default for CommandMap {
             ('e', (scroll_view, "3")),
             ('Y', (shift_view, "-3")),
             ('E', (shift_view, "3")),
+            ('K', (preview_scroll, "-3")),
+            ('J', (preview_scroll, "3")),
             (':', (prompt, ":", "command")),
             ('!', (prompt, "!", "sh")),
             ('<', (prompt, "<", "echo")),
@@ -610,6 +847,80 @@ make_lst! {
         Completer::None,
     );
 
+    bulk_rename = (
+        "rename the marked nodes (or the one at the cursor) by editing their names in $EDITOR, one per line",
+        |mut app: App, _| {
+            let sources = selection_or_cursor(&app);
+            if let Some(it) = archive_entry(&sources) {
+                warn_archive_entry(&mut app, "rename", it);
+                return app;
+            }
+            let names = sources
+                .iter()
+                .map(|it| it.file_name().map(|n| n.to_string_lossy().to_string()))
+                .collect::<Option<Vec<_>>>();
+            let Some(names) = names.filter(|v| !v.iter().any(|n| n.contains('\n'))) else {
+                app.message(Message::Warning("cannot bulk-rename the root or names with new lines".to_string()));
+                return app;
+            };
+
+            let file = match fileop::temp_file("treest-bulk-rename") {
+                Ok((path, mut file)) => match writeln!(file, "{}", names.join("\n")) {
+                    Ok(()) => path,
+                    Err(err) => {
+                        let _ = fs::remove_file(&path);
+                        app.message(Message::Error(format!("could not write '{}': {err}", path.to_string_lossy())));
+                        return app;
+                    }
+                },
+                Err(err) => {
+                    app.message(Message::Error(format!("could not create a temporary file: {err}")));
+                    return app;
+                }
+            };
+
+            app.state = AppState::Pending(Box::new(move |mut app| {
+                let editor = env::var("VISUAL")
+                    .or_else(|_| env::var("EDITOR"))
+                    .unwrap_or_else(|_| "vi".to_string());
+                let mut editor = editor.split_whitespace();
+                let status = SysCommand::new(editor.next().unwrap_or("vi"))
+                    .args(editor)
+                    .arg(&file)
+                    .status();
+                let edited = fs::read_to_string(&file);
+                let _ = fs::remove_file(&file);
+
+                match (status, edited) {
+                    (Ok(status), Ok(edited)) if status.success() => {
+                        let names = edited.lines().collect::<Vec<_>>();
+                        match fileop::plan_renames(&sources, &names) {
+                            Ok(plan) => {
+                                let (done, errors) = fileop::apply_renames(&plan);
+                                let record = Record::Rename(done);
+                                app.fixup_within(&record.paths());
+                                let count = record.paths().len() / 2;
+                                app.journal_mut().record(record);
+                                let summary = format!("renamed {count} node{}", if count != 1 { "s" } else { "" });
+                                app.message(if errors.is_empty() {
+                                    Message::Info(summary)
+                                } else {
+                                    Message::Error(format!("{summary}, but:\n{}", errors.join("\n")))
+                                });
+                            }
+                            Err(err) => app.message(Message::Warning(format!("nothing renamed: {err}"))),
+                        }
+                    }
+                    (Ok(_), Ok(_)) => app.message(Message::Warning("editor exited with a failure, nothing renamed".to_string())),
+                    (Err(err), _) | (_, Err(err)) => app.message(Message::Error(format!("{err}"))),
+                }
+                app
+            }));
+            app
+        },
+        Completer::None,
+    );
+
     cd = (
         "change the current working direcory",
         |mut app, args| {
@@ -625,6 +936,135 @@ make_lst! {
         Completer::FileFromRoot,
     );
 
+    chmod = (
+        "change the mode (octal or symbolic, eg. u+x,go-w) of the marked nodes (or the one at the cursor), recursively with -r; shows a preview to confirm, unless given '--confirm y'",
+        |mut app: App, args: &[&str]| {
+            let mut recursive = false;
+            let mut confirm = None;
+            let mut rest = Vec::new();
+            let mut it = args.iter();
+            while let Some(arg) = it.next() {
+                match *arg {
+                    "-r" | "--recursive" => recursive = true,
+                    "--confirm" => confirm = Some(it.next().copied().unwrap_or("")),
+                    _ => rest.push(*arg),
+                }
+            }
+            let Some((spec, paths)) = rest.split_first() else {
+                app.message(Message::Warning("chmod needs a mode".to_string()));
+                return app;
+            };
+
+            let root = PathBuf::from(app.lookup("root").remove(0));
+            let targets = if paths.is_empty() {
+                selection_or_cursor(&app)
+            } else {
+                paths.iter().map(|it| root.join(it)).collect()
+            };
+            if let Some(it) = archive_entry(&targets) {
+                warn_archive_entry(&mut app, "change the mode of", it);
+                return app;
+            }
+
+            match confirm {
+                None => {
+                    let mut changes = Vec::new();
+                    for it in &targets {
+                        let res = perms::mode_of(it).and_then(|mode| {
+                            perms::parse_mode(spec, mode, it.is_dir())
+                                .map(|niw| (mode, niw))
+                                .map_err(io::Error::other)
+                        });
+                        match res {
+                            Ok((mode, niw)) => changes.push(format!(
+                                "{} -> {}  {}",
+                                mode_to_string(mode),
+                                mode_to_string(niw),
+                                it.strip_prefix(&root).unwrap_or(it).to_string_lossy()
+                            )),
+                            Err(err) => {
+                                app.message(Message::Warning(format!("{}: {err}", it.to_string_lossy())));
+                                return app;
+                            }
+                        }
+                    }
+                    if recursive {
+                        changes.push("(and everything within)".to_string());
+                    }
+                    app.message(Message::Info(changes.join("\n")));
+                    let mut bound = vec![spec.to_string()];
+                    bound.extend(targets.iter().map(|it| it.to_string_lossy().to_string()));
+                    if recursive {
+                        bound.push("--recursive".to_string());
+                    }
+                    bound.push("--confirm".to_string());
+                    app.prompt("apply? (y/n): ".to_string(), Action::Bind(&chmod, bound), None);
+                }
+
+                Some(answer) if !answer.starts_with('y') => {
+                    app.message(Message::Info("nothing changed".to_string()));
+                }
+
+                Some(_) => {
+                    let errors = targets
+                        .iter()
+                        .flat_map(|it| perms::walk(it, recursive, &|path| perms::set_mode(path, spec)))
+                        .collect::<Vec<_>>();
+                    app.fixup_within(&targets);
+                    if !errors.is_empty() {
+                        app.message(Message::Error(errors.join("\n")));
+                    }
+                }
+            }
+            app
+        },
+        Completer::StaticNth(&[Completer::None, Completer::FileFromRoot]),
+    );
+
+    chown = (
+        "change the owner (user, user:group or :group) of the marked nodes (or the one at the cursor), recursively with -r",
+        |mut app: App, args: &[&str]| {
+            let recursive = args.iter().any(|it| matches!(*it, "-r" | "--recursive"));
+            let rest = args
+                .iter()
+                .filter(|it| !matches!(**it, "-r" | "--recursive"))
+                .collect::<Vec<_>>();
+            let Some((spec, paths)) = rest.split_first() else {
+                app.message(Message::Warning("chown needs an owner".to_string()));
+                return app;
+            };
+            let (uid, gid) = match perms::parse_owner(spec) {
+                Ok(ids) => ids,
+                Err(err) => {
+                    app.message(Message::Warning(err));
+                    return app;
+                }
+            };
+
+            let root = PathBuf::from(app.lookup("root").remove(0));
+            let targets = if paths.is_empty() {
+                selection_or_cursor(&app)
+            } else {
+                paths.iter().map(|it| root.join(it)).collect()
+            };
+            if let Some(it) = archive_entry(&targets) {
+                warn_archive_entry(&mut app, "change the owner of", it);
+                return app;
+            }
+
+            let errors = targets
+                .iter()
+                .flat_map(|it| perms::walk(it, recursive, &|path| perms::set_owner(path, uid, gid)))
+                .collect::<Vec<_>>();
+            app.fixup_within(&targets);
+            if !errors.is_empty() {
+                app.message(Message::Error(errors.join("\n")));
+            }
+            app
+        },
+        Completer::StaticNth(&[Completer::None, Completer::FileFromRoot]),
+    );
+
     close_other_splits = (
         "close every other views, keeping only the current one",
         |mut app: App, _| {
@@ -671,6 +1111,119 @@ make_lst! {
         ]),
     );
 
+    copy = (
+        "copy the marked nodes (or the one at the cursor) into a directory (by default the one under the cursor), on conflict: skip/overwrite (to the trash)/rename",
+        |app: App, args: &[&str]| file_operation(app, Operation::Copy, "copy", args),
+        Completer::FileFromRoot,
+    );
+
+    delete = (
+        "delete the marked nodes (or the one at the cursor) by moving them to the trash, or for good with --permanent (asks for confirmation, unless given '--confirm y')",
+        |mut app: App, args: &[&str]| {
+            if app.busy() {
+                app.message(Message::Warning("an operation is already in progress".to_string()));
+                return app;
+            }
+
+            let mut permanent = false;
+            let mut confirm = None;
+            let mut paths = Vec::new();
+            let mut it = args.iter();
+            while let Some(arg) = it.next() {
+                match *arg {
+                    "-p" | "--permanent" => permanent = true,
+                    "--confirm" => confirm = Some(it.next().copied().unwrap_or("")),
+                    _ => paths.push(arg),
+                }
+            }
+
+            let root = PathBuf::from(app.lookup("root").remove(0));
+            let targets = if paths.is_empty() {
+                selection_or_cursor(&app)
+            } else {
+                paths.into_iter().map(|it| root.join(it)).collect()
+            };
+            if let Some(it) = archive_entry(&targets) {
+                warn_archive_entry(&mut app, "delete", it);
+                return app;
+            }
+            if targets.contains(&root) {
+                app.message(Message::Warning("cannot delete the root".to_string()));
+                return app;
+            }
+
+            match confirm {
+                None => {
+                    app.message(Message::Info(format!(
+                        "{}:\n{}",
+                        if permanent { "will be deleted permanently" } else { "will be moved to the trash" },
+                        targets
+                            .iter()
+                            .map(|it| it.strip_prefix(&root).unwrap_or(it).to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    )));
+                    let mut bound = targets
+                        .iter()
+                        .map(|it| it.to_string_lossy().to_string())
+                        .collect::<Vec<_>>();
+                    if permanent {
+                        bound.push("--permanent".to_string());
+                    }
+                    bound.push("--confirm".to_string());
+                    app.prompt(
+                        format!(
+                            "delete {} node{}? (y/n): ",
+                            targets.len(),
+                            if targets.len() != 1 { "s" } else { "" }
+                        ),
+                        Action::Bind(&delete, bound),
+                        None,
+                    );
+                }
+
+                Some(answer) if !answer.starts_with('y') => {
+                    app.message(Message::Info("nothing deleted".to_string()));
+                }
+
+                Some(_) => {
+                    let mut errors = Vec::new();
+                    let mut deleted = Vec::new();
+                    let mut trashed = Vec::new();
+                    for it in &targets {
+                        let res = if permanent {
+                            fileop::remove(it).map(|()| deleted.push(it.clone()))
+                        } else {
+                            trash::put(it).map(|name| trashed.push((it.clone(), name)))
+                        };
+                        if let Err(err) = res {
+                            errors.push(format!("{}: {err}", it.to_string_lossy()));
+                        }
+                    }
+                    app.fixup_within(&targets);
+                    let count = targets.len() - errors.len();
+                    app.journal_mut().record(if permanent {
+                        Record::Delete(deleted)
+                    } else {
+                        Record::Trash(trashed)
+                    });
+                    let summary = format!(
+                        "{} {count} node{}",
+                        if permanent { "deleted" } else { "trashed" },
+                        if count != 1 { "s" } else { "" }
+                    );
+                    app.message(if errors.is_empty() {
+                        Message::Info(summary)
+                    } else {
+                        Message::Error(format!("{summary}, but:\n{}", errors.join("\n")))
+                    });
+                }
+            }
+            app
+        },
+        Completer::FileFromRoot,
+    );
+
     echo = (
         "echo the arguments to standard output, usually to be captured by the calling process (eg. in shell script)",
         |app: App, args: &[&str]| {
@@ -713,6 +1266,53 @@ make_lst! {
         Completer::StaticWords(COMMAND_LIST),
     );
 
+    extract_to = (
+        "extract the marked entries of archives (or the one at the cursor, everything if it is the archive itself) into a directory (by default the one containing the archive)",
+        |mut app: App, args: &[&str]| {
+            let root = PathBuf::from(app.lookup("root").remove(0));
+            let dest = match args {
+                [] => None,
+                [dest] => Some(root.join(dest)),
+                _ => {
+                    app.message(Message::Warning(
+                        "extract_to takes at most a destination directory".to_string(),
+                    ));
+                    return app;
+                }
+            };
+            let mut created = Vec::new();
+            let mut errors = Vec::new();
+            for it in selection_or_cursor(&app) {
+                let Some((archive, within)) = archive::split(&it) else {
+                    errors.push(format!("{}: not in an archive", it.to_string_lossy()));
+                    continue;
+                };
+                let dest = dest
+                    .clone()
+                    .unwrap_or_else(|| archive.parent().unwrap().to_path_buf());
+                match archive::extract(archive, within, &dest) {
+                    Ok((paths, skipped)) => {
+                        created.extend(paths);
+                        errors.extend(skipped.iter().map(|rel| {
+                            format!("{}: links are not extracted", archive.join(rel).to_string_lossy())
+                        }));
+                    }
+                    Err(err) => errors.push(format!("{}: {err}", it.to_string_lossy())),
+                }
+            }
+
+            app.fixup_within(&created);
+            if let Some(rel) = created.last().and_then(|it| it.strip_prefix(&root).ok()) {
+                app.reveal_everywhere(rel);
+            }
+            if !errors.is_empty() {
+                app.message(Message::Error(errors.join("\n")));
+            }
+            app
+        },
+        Completer::FileFromRoot,
+    );
+
     filter = (
         "add or remove filters",
         |mut app: App, args: &[&str]| {
@@ -741,6 +1341,13 @@ make_lst! {
                             ye
                         },
                         "dotfiles" => Filtering::new_pattern(".*".to_string()),
+                        "kind" => {
+                            let Some(pat) = args.get(2) else {
+                                app.message(Message::Warning("missing kind (eg. 'image/*' or 'elf')".to_string()));
+                                return app;
+                            };
+                            Filtering::Kind(pat.to_string())
+                        }
                         incorrect => {
                             app.message(Message::Warning(format!(
                                 "incorrect filter type: {incorrect:?}"
@@ -788,7 +1395,7 @@ make_lst! {
         },
         Completer::StaticNth(&[
             Completer::StaticWords(&["add", "remove", "clear", "list"]),
-            Completer::StaticWords(&["pattern", "file", "dotfiles"]),
+            Completer::StaticWords(&["pattern", "file", "dotfiles", "kind"]),
             Completer::None,
         ]),
     );
@@ -885,7 +1492,7 @@ make_lst! {
         "find the next node, which name contains the search string, in the folder the cursor is in",
         |mut app: App, _| {
             let lu = app.lookup("search");
-            let Some(search) = lu.get(0) else {
+            let Some(search) = lu.first() else {
                 app.message(Message::Warning(
                     "no previous search for find_in_next".to_string()
                 ));
@@ -929,7 +1536,7 @@ make_lst! {
         "find the previous node, which name contains the search string, in the folder the cursor is in",
         |mut app: App, _| {
             let lu = app.lookup("search");
-            let Some(search) = lu.get(0) else {
+            let Some(search) = lu.first() else {
                 app.message(Message::Warning(
                     "no previous search for find_in_prev".to_string()
                 ));
@@ -968,6 +1575,27 @@ make_lst! {
         Completer::None,
     );
 
+    flatten = (
+        "toggle listing every file under the directory at the cursor as a flat list (sorted across the whole subtree)",
+        |mut app: App, _| {
+            let (view, tree) = app.focused_and_tree_mut();
+            if let Err(err) = view.toggle_flatten(tree) {
+                app.message(Message::Error(format!("{err}")));
+            }
+            app
+        },
+        Completer::None,
+    );
+
+    fold_all = (
+        "fold every node, moving the cursor up to the top level",
+        |mut app: App, _| {
+            app.focused_mut().fold_all();
+            app
+        },
+        Completer::None,
+    );
+
     fold_node = (
         "fold the node at the cursor",
         |mut app: App, _| {
@@ -977,6 +1605,39 @@ make_lst! {
         Completer::None,
     );
 
+    fold_others = (
+        "fold every node that is not on the path to the cursor",
+        |mut app: App, _| {
+            app.focused_mut().fold_others();
+            app
+        },
+        Completer::None,
+    );
+
+    fold_rec = (
+        "fold the node at the cursor and everything within it",
+        |mut app: App, _| {
+            app.focused_mut().fold_rec();
+            app
+        },
+        Completer::None,
+    );
+
+    fuzzy = (
+        "open a fuzzy finder over every path under the root, accepting a result reveals it",
+        |mut app: App, _| {
+            app.fuzzy_open();
+            app
+        },
+        Completer::None,
+    );
+
+    hardlink = (
+        "create hard links to the marked nodes (or the one at the cursor) in a directory (by default the one under the cursor, if not where the nodes are)",
+        |app: App, args: &[&str]| link_nodes(app, "hardlink", args, |target, path| fs::hard_link(target, path)),
+        Completer::FileFromRoot,
+    );
+
     help = (
         "get help for a command or a list of known commands",
         |mut app: App, args: &[&str]| {
@@ -999,6 +1660,37 @@ make_lst! {
         Completer::StaticWords(COMMAND_LIST),
     );
 
+    info = (
+        "show everything about the node at the cursor, or at the given path, in a popup (full and canonical paths, links, inode, owner, times, mode, xattrs...)",
+        |mut app: App, args: &[&str]| {
+            let path = match args.first() {
+                Some(it) => PathBuf::from(app.lookup("root").remove(0)).join(it),
+                None => PathBuf::from(app.lookup("").remove(0)),
+            };
+            let (_, tree) = app.focused_and_tree();
+            match info::describe(tree.root.source().as_ref(), &path) {
+                Ok(lines) => app.info_open(info::Popup::new(&path, lines)),
+                Err(err) => app.message(Message::Error(format!("{}: {err}", path.to_string_lossy()))),
+            }
+            app
+        },
+        Completer::FileFromRoot,
+    );
+
+    journal = (
+        "list the file operations (copy, move, rename, delete...) done so far in this session",
+        |mut app: App, _| {
+            let list = app.journal_mut().list();
+            app.message(Message::Info(if list.is_empty() {
+                "no file operation yet".to_string()
+            } else {
+                list.join("\n")
+            }));
+            app
+        },
+        Completer::None,
+    );
+
     keys = (
         "send key events as if theses where pressed by the user (whatever you are doing, this should really be last resort)",
         |app: App, args: &[&str]| {
@@ -1022,6 +1714,147 @@ make_lst! {
         Completer::None,
     );
 
+    link = (
+        "create symbolic links to the marked nodes (or the one at the cursor) in a directory (by default the one under the cursor, if not where the nodes are)",
+        |app: App, args: &[&str]| {
+            link_nodes(app, "link", args, |target, path| {
+                #[cfg(unix)]
+                return std::os::unix::fs::symlink(target, path);
+                #[cfg(windows)]
+                return if target.is_dir() {
+                    std::os::windows::fs::symlink_dir(target, path)
+                } else {
+                    std::os::windows::fs::symlink_file(target, path)
+                };
+            })
+        },
+        Completer::FileFromRoot,
+    );
+
+    mark = (
+        "manipulate marks: clear, invert [-r], and named sets with save/load/drop/list and union/intersection/difference",
+        |mut app: App, args: &[&str]| {
+            let (view, tree) = app.focused_and_tree_mut();
+            match args {
+                ["clear", ..] => view.clear_marked(),
+                ["invert", rest @ ..] => view.invert_marked(rest.first() == Some(&"-r")),
+                ["list", ..] | [] => {
+                    let mut l = view
+                        .list_selections()
+                        .iter()
+                        .map(|(name, paths)| format!("{name} ({} nodes)", paths.len()))
+                        .collect::<Vec<_>>();
+                    l.sort_unstable();
+                    app.message(Message::Info(if l.is_empty() {
+                        "# (no selection)".to_string()
+                    } else {
+                        format!("# selections:\n{}", l.join("\n"))
+                    }));
+                }
+                [does @ ("save" | "load" | "drop" | "union" | "intersection" | "difference")] => {
+                    app.message(Message::Warning(format!("mark {does} needs a selection name")));
+                }
+                ["save", name, ..] => {
+                    let paths = view.collect_marked_paths(tree);
+                    view.save_selection(name, paths);
+                }
+                ["drop", name, ..] => {
+                    if !view.drop_selection(name) {
+                        app.message(Message::Warning(format!("no selection named '{name}'")));
+                    }
+                }
+                [does @ ("load" | "union" | "intersection" | "difference"), name, ..] => {
+                    let Some(named) = view.get_selection(name).cloned() else {
+                        app.message(Message::Warning(format!("no selection named '{name}'")));
+                        return app;
+                    };
+                    let current = view.collect_marked_paths(tree);
+                    let paths = match *does {
+                        "load" => named,
+                        "union" => {
+                            let mut r = current;
+                            r.extend(named.into_iter().filter(|it| !r.contains(it)).collect::<Vec<_>>());
+                            r
+                        }
+                        "intersection" => current.into_iter().filter(|it| named.contains(it)).collect(),
+                        "difference" => current.into_iter().filter(|it| !named.contains(it)).collect(),
+                        _ => unreachable!(),
+                    };
+                    let missing = view.set_marked_paths(tree, &paths);
+                    if 0 < missing {
+                        app.message(Message::Warning(format!(
+                            "{missing} node{} could not be found",
+                            if missing != 1 { "s" } else { "" }
+                        )));
+                    }
+                }
+                [incorrect, ..] => {
+                    app.message(Message::Warning(format!(
+                        "incorrect action for mark: {incorrect:?}"
+                    )));
+                }
+            }
+            app
+        },
+        Completer::StaticNth(&[
+            Completer::StaticWords(&[
+                "clear",
+                "invert",
+                "list",
+                "save",
+                "load",
+                "drop",
+                "union",
+                "intersection",
+                "difference",
+            ]),
+            Completer::Lookup("selections"),
+        ]),
+    );
+
+    mark_glob = (
+        "mark nodes which name matches the pattern, in the folder the cursor is in or its whole subtree with -r",
+        |mut app: App, args: &[&str]| {
+            let (recursive, pat) = match args {
+                ["-r", pat, ..] => (true, pat),
+                [pat, ..] => (false, pat),
+                [] => {
+                    app.message(Message::Warning("mark-glob needs a pattern".to_string()));
+                    return app;
+                }
+            };
+            let Ok(search) = Pattern::new(pat) else {
+                app.message(Message::Warning(format!("error parsing the pattern '{pat}'")));
+                return app;
+            };
+            mark_matching(app, recursive, &|node| search.matches(node.file_name()))
+        },
+        Completer::StaticNth(&[Completer::StaticWords(&["-r"]), Completer::None]),
+    );
+
+    mark_regex = (
+        "mark nodes which name matches the regular expression, in the folder the cursor is in or its whole subtree with -r",
+        |mut app: App, args: &[&str]| {
+            let (recursive, re) = match args {
+                ["-r", re, ..] => (true, re),
+                [re, ..] => (false, re),
+                [] => {
+                    app.message(Message::Warning("mark-regex needs a regular expression".to_string()));
+                    return app;
+                }
+            };
+            let search = match Regex::new(re) {
+                Ok(search) => search,
+                Err(err) => {
+                    app.message(Message::Warning(format!("error parsing the regular expression: {err}")));
+                    return app;
+                }
+            };
+            mark_matching(app, recursive, &|node| search.is_match(node.file_name()))
+        },
+        Completer::StaticNth(&[Completer::StaticWords(&["-r"]), Completer::None]),
+    );
+
     message = (
         "display a message, level should be one of info/warning/error",
         |mut app: App, args: &[&str]| {
@@ -1038,6 +1871,77 @@ make_lst! {
         Completer::StaticWords(&["info", "warning", "error"]),
     );
 
+    meta_format = (
+        "change how sizes (bytes/iec/si) and times (raw/relative/iso) are displayed",
+        |mut app: App, args: &[&str]| {
+            match args {
+                ["size", f, ..] => {
+                    app.meta_format_mut().size = match *f {
+                        "bytes" => SizeFormat::Bytes,
+                        "iec" => SizeFormat::Iec,
+                        "si" => SizeFormat::Si,
+                        unk => {
+                            app.message(Message::Warning(format!("unknown size format '{unk}'")));
+                            return app;
+                        }
+                    }
+                }
+                ["time", f, ..] => {
+                    app.meta_format_mut().time = match *f {
+                        "raw" => TimeFormat::Raw,
+                        "relative" => TimeFormat::Relative,
+                        "iso" => TimeFormat::Iso,
+                        unk => {
+                            app.message(Message::Warning(format!("unknown time format '{unk}'")));
+                            return app;
+                        }
+                    }
+                }
+                [] => {
+                    let f = app.meta_format();
+                    app.message(Message::Info(format!(
+                        "size {}\ntime {}",
+                        match f.size {
+                            SizeFormat::Bytes => "bytes",
+                            SizeFormat::Iec => "iec",
+                            SizeFormat::Si => "si",
+                        },
+                        match f.time {
+                            TimeFormat::Raw => "raw",
+                            TimeFormat::Relative => "relative",
+                            TimeFormat::Iso => "iso",
+                        },
+                    )));
+                }
+                _ => app.message(Message::Warning(
+                    "meta-format needs 'size' or 'time' and a format".to_string(),
+                )),
+            }
+            app
+        },
+        Completer::StaticNth(&[
+            Completer::StaticWords(&["size", "time"]),
+            Completer::Defered(|args, _, _| match args[0] {
+                "size" => Completer::StaticWords(&["bytes", "iec", "si"]),
+                "time" => Completer::StaticWords(&["raw", "relative", "iso"]),
+                _ => Completer::None,
+            }),
+            Completer::None,
+        ]),
+    );
+
+    mkdir = (
+        "create directories (and intermediate ones) at the given paths, from the folder under the cursor",
+        |app: App, args: &[&str]| create_nodes(app, "mkdir", args, |path| fs::create_dir_all(path)),
+        Completer::FileFromCursor,
+    );
+
+    r#move = (
+        "move the marked nodes (or the one at the cursor) into a directory (by default the one under the cursor), on conflict: skip/overwrite (to the trash)/rename",
+        |app: App, args: &[&str]| file_operation(app, Operation::Move, "move", args),
+        Completer::FileFromRoot,
+    );
+
     next_node = (
         "move the cursor to the next node",
         |mut app: App, _| {
@@ -1047,6 +1951,27 @@ make_lst! {
         Completer::None,
     );
 
+    preview = (
+        "toggle the preview of the node at the cursor, in a split next to the view (first lines of a file, listing of a directory)",
+        |mut app: App, _| {
+            app.preview_toggle();
+            app
+        },
+        Completer::None,
+    );
+
+    preview_scroll = (
+        "scroll the preview (vertically) without leaving the view",
+        |mut app: App, args: &[&str]| {
+            let by = args.first().map_or(Ok(1), |n| n.parse()).unwrap_or(1);
+            if !app.preview_scroll(by) {
+                app.message(Message::Info("no preview, see :preview".to_string()));
+            }
+            app
+        },
+        Completer::None,
+    );
+
     prev_node = (
         "move the cursor to the previous node",
         |mut app: App, _| {
@@ -1151,6 +2076,25 @@ make_lst! {
         Completer::None,
     );
 
+    redo = (
+        "redo the last undone file operation",
+        |mut app: App, _| {
+            match app.journal_mut().redo() {
+                None => app.message(Message::Info("nothing to redo".to_string())),
+                Some((record, errors)) => {
+                    app.fixup_within(&record.paths());
+                    app.message(if errors.is_empty() {
+                        Message::Info(format!("redo: {}", record.describe()))
+                    } else {
+                        Message::Error(format!("redo: {}, but:\n{}", record.describe(), errors.join("\n")))
+                    });
+                }
+            }
+            app
+        },
+        Completer::None,
+    );
+
     reload = (
         "reload the tree from the file system and update every views",
         |mut app: App, _| {
@@ -1160,8 +2104,56 @@ make_lst! {
         Completer::None,
     );
 
+    rename = (
+        "rename the node at the cursor, prompting with its current name if no new name is given",
+        |mut app: App, args: &[&str]| {
+            let from = PathBuf::from(app.lookup("").remove(0));
+            let root = PathBuf::from(app.lookup("root").remove(0));
+            if from == root {
+                app.message(Message::Warning("cannot rename the root".to_string()));
+                return app;
+            }
+            if archive_entry(std::slice::from_ref(&from)).is_some() {
+                warn_archive_entry(&mut app, "rename", &from);
+                return app;
+            }
+            let name = from.file_name().unwrap().to_string_lossy().to_string();
+
+            match args {
+                [] => {
+                    // cursor right before the extension, if any
+                    let stem = match name.rfind('.') {
+                        Some(k) if 0 < k => &name[..k],
+                        _ => &name,
+                    };
+                    app.prompt("rename: ".to_string(), Action::Bind(&rename, Vec::new()), Some(&line_escape(&name)));
+                    app.prompt_cursor(line_escape(stem).chars().count());
+                }
+
+                [new] => match fileop::plan_renames(std::slice::from_ref(&from), &[new]) {
+                    Ok(plan) => {
+                        let (done, errors) = fileop::apply_renames(&plan);
+                        if let Some(err) = errors.first() {
+                            app.message(Message::Error(err.clone()));
+                        } else if let Some((_, to)) = done.first() {
+                            let to = to.clone();
+                            app.fixup_within(&[from.clone(), to.clone()]);
+                            app.reveal(to.strip_prefix(&root).unwrap());
+                            app.journal_mut().record(Record::Rename(done));
+                        }
+                    }
+                    Err(err) => app.message(Message::Warning(format!("could not rename '{name}': {err}"))),
+                },
+
+                _ => app.message(Message::Warning("rename takes a single new name (quote it if it contains spaces)".to_string())),
+            }
+            app
+        },
+        Completer::None,
+    );
+
     reroot = (
-        "change the root of the tree, by default to the current working directory",
+        "change the root of the tree, by default to the current working directory (a file is opened as a JSON, TOML or YAML document)",
         |mut app, args| {
             let cwd = current_dir().unwrap();
             let dir = match dunce::canonicalize({
@@ -1212,6 +2204,33 @@ make_lst! {
         Completer::None,
     );
 
+    reveal = (
+        "unfold every folder down to the given path (from the root) and move the cursor onto it",
+        |mut app: App, args: &[&str]| {
+            let Some(path) = args.first() else {
+                app.message(Message::Warning("reveal needs a path".to_string()));
+                return app;
+            };
+            let root = app.lookup("root").remove(0);
+            let path = PathBuf::from(path);
+            let rel = if path.is_absolute() {
+                let Ok(rel) = path.strip_prefix(&root) else {
+                    app.message(Message::Warning(format!(
+                        "'{}' is not under the root",
+                        path.to_string_lossy()
+                    )));
+                    return app;
+                };
+                rel.to_path_buf()
+            } else {
+                path
+            };
+            app.reveal(&rel);
+            app
+        },
+        Completer::FileFromRoot,
+    );
+
     scroll_view = (
         "scroll the focused view (vertically)",
         |mut app: App, args: &[&str]| {
@@ -1478,9 +2497,14 @@ make_lst! {
     );
 
     toggle_marked = (
-        "mark/unmark the node at the cursor",
+        "mark/unmark the node at the cursor (or every node in the range when in visual mode)",
         |mut app: App, _| {
-            app.focused_mut().toggle_marked();
+            let view = app.focused_mut();
+            if view.in_visual() {
+                view.visual_apply(|st| st.marked = !st.marked);
+            } else {
+                view.toggle_marked();
+            }
             app
         },
         Completer::None,
@@ -1519,6 +2543,23 @@ make_lst! {
         Completer::None,
     );
 
+    touch = (
+        "create empty files (and intermediate directories) at the given paths, from the folder under the cursor, or update their modification time",
+        |app: App, args: &[&str]| {
+            create_nodes(app, "touch", args, |path| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?
+                    .set_modified(SystemTime::now())
+            })
+        },
+        Completer::FileFromCursor,
+    );
+
     transpose_splits = (
         "transpose the split containing the focused view",
         |mut app: App, _| {
@@ -1528,6 +2569,118 @@ make_lst! {
         Completer::None,
     );
 
+    trash = (
+        "list the content of the trash (most recent first), or restore entries to where they were deleted from (by default the last deleted ones)",
+        |mut app: App, args: &[&str]| {
+            let entries = match trash::list() {
+                Ok(entries) => entries,
+                Err(err) => {
+                    app.message(Message::Error(format!("could not read the trash: {err}")));
+                    return app;
+                }
+            };
+
+            match args {
+                [] | ["list"] => app.message(Message::Info(if entries.is_empty() {
+                    "the trash is empty".to_string()
+                } else {
+                    entries
+                        .iter()
+                        .map(|it| format!("{}  {}", it.date, it.path.to_string_lossy()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })),
+
+                ["restore", paths @ ..] => {
+                    let root = PathBuf::from(app.lookup("root").remove(0));
+                    let chosen = if paths.is_empty() {
+                        entries
+                            .iter()
+                            .take_while(|it| it.date == entries[0].date)
+                            .collect::<Vec<_>>()
+                    } else {
+                        let mut chosen = Vec::new();
+                        for path in paths {
+                            let path = root.join(path);
+                            match entries.iter().find(|it| it.path == path) {
+                                Some(it) => chosen.push(it),
+                                None => {
+                                    app.message(Message::Warning(format!(
+                                        "'{}' is not in the trash",
+                                        path.to_string_lossy()
+                                    )));
+                                    return app;
+                                }
+                            }
+                        }
+                        chosen
+                    };
+
+                    let mut errors = Vec::new();
+                    let mut restored = Vec::new();
+                    for it in chosen {
+                        match trash::restore(it) {
+                            Ok(()) => restored.push((it.path.clone(), it.name.clone())),
+                            Err(err) => errors.push(format!("{}: {err}", it.path.to_string_lossy())),
+                        }
+                    }
+                    let changed = restored.iter().map(|(it, _)| it.clone()).collect::<Vec<_>>();
+                    app.fixup_within(&changed);
+                    app.journal_mut().record(Record::Restore(restored));
+                    let summary = format!(
+                        "restored {} node{}",
+                        changed.len(),
+                        if changed.len() != 1 { "s" } else { "" }
+                    );
+                    app.message(if errors.is_empty() {
+                        Message::Info(summary)
+                    } else {
+                        Message::Error(format!("{summary}, but:\n{}", errors.join("\n")))
+                    });
+                }
+
+                _ => app.message(Message::Warning("trash expects 'list' or 'restore' and optional paths".to_string())),
+            }
+            app
+        },
+        Completer::StaticNth(&[
+            Completer::StaticWords(&["list", "restore"]),
+            Completer::Fn(|args, arg_idx, ch_idx| {
+                let word = args[arg_idx];
+                let (k, _) = word.char_indices().nth(ch_idx).unwrap_or((word.len(), ' '));
+                let mut r = trash::list()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|it| it.path.to_string_lossy().to_string())
+                    .filter(|it| it.starts_with(&word[..k]))
+                    .map(|it| it + " ")
+                    .collect::<Vec<_>>();
+                r.sort_unstable();
+                r.dedup();
+                r
+            }),
+        ]),
+    );
+
+    undo = (
+        "undo the last file operation where possible (eg. moving back or restoring from the trash)",
+        |mut app: App, _| {
+            match app.journal_mut().undo() {
+                None => app.message(Message::Info("nothing to undo".to_string())),
+                Some((record, errors)) => {
+                    app.fixup_within(&record.paths());
+                    app.message(if errors.is_empty() {
+                        Message::Info(format!("undo: {}", record.describe()))
+                    } else {
+                        Message::Error(format!("undo: {}, but:\n{}", record.describe(), errors.join("\n")))
+                    });
+                }
+            }
+            app
+        },
+        Completer::None,
+    );
+
     unfold_node = (
         "try to unfold the node at the cursor (nothing happens if it is not a directory or a link to one)",
         |mut app: App, _| {
@@ -1538,6 +2691,27 @@ make_lst! {
         Completer::None,
     );
 
+    unfold_rec = (
+        "unfold the node at the cursor and its sub-directories recursively, optionally up to a depth (filtered nodes and link cycles are skipped)",
+        |mut app: App, args: &[&str]| {
+            let depth = match args.first().map(|n| n.parse()) {
+                None => usize::MAX,
+                Some(Ok(n)) => n,
+                Some(Err(_)) => {
+                    app.message(Message::Warning(format!("'{}' is not a valid depth", args[0])));
+                    return app;
+                }
+            };
+            let (view, tree) = app.focused_and_tree_mut();
+            let errors = view.unfold_rec(tree, depth);
+            if !errors.is_empty() {
+                app.message(Message::Error(errors.join("\n")));
+            }
+            app
+        },
+        Completer::None,
+    );
+
     var = (
         "declare a variable, giving it a value",
         |mut app: App, args: &[&str]| {
@@ -1576,4 +2750,68 @@ make_lst! {
         Completer::None,
     );
 
+    view_redo = (
+        "redo the last undone change to the view (see view-undo)",
+        |mut app: App, _| {
+            let (view, tree) = app.focused_and_tree_mut();
+            if !view.redo(tree) {
+                app.message(Message::Info("nothing to redo".to_string()));
+            }
+            app
+        },
+        Completer::None,
+    );
+
+    view_undo = (
+        "undo the last change to the view (folding, marking, filtering, sorting...), the cursor is also put back",
+        |mut app: App, _| {
+            let (view, tree) = app.focused_and_tree_mut();
+            if !view.undo(tree) {
+                app.message(Message::Info("nothing to undo".to_string()));
+            }
+            app
+        },
+        Completer::None,
+    );
+
+    visual = (
+        "enter/leave visual mode (selecting a range of nodes from the cursor), or mark/unmark/toggle every node in the range",
+        |mut app: App, args: &[&str]| {
+            let view = app.focused_mut();
+            match args.first() {
+                None if view.in_visual() => view.visual_cancel(),
+                None => view.visual_start(),
+                Some(&"mark") => view.visual_apply(|st| st.marked = true),
+                Some(&"unmark") => view.visual_apply(|st| st.marked = false),
+                Some(&"toggle") => view.visual_apply(|st| st.marked = !st.marked),
+                Some(unk) => app.message(Message::Warning(format!(
+                    "incorrect action for visual: {unk:?}"
+                ))),
+            }
+            app
+        },
+        Completer::StaticWords(&["mark", "unmark", "toggle"]),
+    );
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(Ok('r'.into()), Key::try_from("r"));
+        assert_eq!(
+            Ok(Key {
+                kch: KeyCode::Char('r'),
+                kmod: KeyModifiers::CONTROL,
+            }),
+            Key::try_from("<C-r>")
+        );
+        assert_eq!(Err(()), Key::try_from("<X-r>"));
+        assert_eq!(Err(()), Key::try_from(""));
+        // (the default bindings are all valid keys)
+        let _ = CommandMap::default();
+    }
 }
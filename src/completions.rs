@@ -32,6 +32,8 @@ pub enum Completer {
     Defered(fn(&[&str], usize, usize) -> Completer),
     Words(Vec<String>),
     StaticWords(&'static [&'static str]),
+    /// words from looking up a variable (eg. "selections")
+    Lookup(&'static str),
     Of(&'static StaticCommand, usize),
     Nth(Vec<Completer>),
     StaticNth(&'static [Completer]),
@@ -57,6 +59,8 @@ impl fmt::Display for Completer {
 
             Completer::StaticWords(l) => write!(f, "{indent}one of: {}", l.join(" ")),
 
+            Completer::Lookup(name) => write!(f, "{indent}one of the {name}"),
+
             Completer::Of(sc, _shift) => {
                 write!(f, "{indent}{:.*}", depth + 1, sc.get_comp_itself())
             }
@@ -131,6 +135,10 @@ impl Completer {
                 r
             }
 
+            Completer::Lookup(name) => {
+                Completer::Words(lookup(name)).get_comp(args, arg_idx, ch_idx, lookup)
+            }
+
             Completer::Of(sc, shift) => {
                 sc.get_comp(&args[*shift..], arg_idx - shift, ch_idx, lookup)
             }
@@ -191,7 +199,7 @@ impl Completer {
                             r.dedup();
                             r
                         })
-                        .unwrap_or(Vec::new())
+                        .unwrap_or_default()
                 }
             }
 
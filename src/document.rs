@@ -0,0 +1,167 @@
+// structured documents (JSON, TOML, YAML) read as trees, where
+// objects and arrays are folders and scalars are leafs
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::{fs, io, path::Path};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Kind {
+    Object,
+    Array,
+    /// as it would be written in JSON
+    Scalar(String),
+}
+
+/// what is known of a value within a document
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Member {
+    /// JSON pointer from the document's root (RFC 6901)
+    pub pointer: String,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+fn format_of(path: &Path) -> Option<Format> {
+    match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "json" => Some(Format::Json),
+        "toml" => Some(Format::Toml),
+        "yaml" | "yml" => Some(Format::Yaml),
+        _ => None,
+    }
+}
+
+/// from the name only, the content is not checked
+pub fn is_document(path: &Path) -> bool {
+    format_of(path).is_some()
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(n) => Value::Number(n.into()),
+        toml::Value::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(v) => Value::Array(v.into_iter().map(from_toml).collect()),
+        toml::Value::Table(t) => {
+            Value::Object(t.into_iter().map(|(k, v)| (k, from_toml(v))).collect())
+        }
+    }
+}
+
+fn from_yaml(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Bool(b),
+        serde_yaml::Value::Number(n) => {
+            if let Some(n) = n.as_i64() {
+                Value::Number(n.into())
+            } else if let Some(n) = n.as_u64() {
+                Value::Number(n.into())
+            } else {
+                n.as_f64()
+                    .and_then(Number::from_f64)
+                    .map_or(Value::Null, Value::Number)
+            }
+        }
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(v) => Value::Array(v.into_iter().map(from_yaml).collect()),
+        serde_yaml::Value::Mapping(m) => Value::Object(
+            m.into_iter()
+                .map(|(k, v)| {
+                    // keys are not necessarily strings in YAML
+                    let k = match from_yaml(k) {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (k, from_yaml(v))
+                })
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+fn parse(text: &str, format: Format) -> Result<Value, String> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|err| err.to_string()),
+        Format::Toml => toml::from_str(text)
+            .map(from_toml)
+            .map_err(|err| err.to_string()),
+        Format::Yaml => serde_yaml::from_str(text)
+            .map(from_yaml)
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// read the document at `path`; when the format is not known from
+/// the extension, each one is tried in turn
+pub fn read(path: &Path) -> io::Result<Value> {
+    let text = fs::read_to_string(path)?;
+    match format_of(path) {
+        Some(format) => parse(&text, format),
+        None => [Format::Json, Format::Toml, Format::Yaml]
+            .into_iter()
+            .find_map(|format| parse(&text, format).ok())
+            .ok_or_else(|| "not a JSON, TOML or YAML document".to_string()),
+    }
+    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// a key as a reference token in a JSON pointer
+pub fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// a key as the name of a node (which must be usable as a path
+/// component), ie. escaped as in a pointer, and quoted if empty or
+/// dots only
+pub fn name(key: &str) -> String {
+    match key {
+        "" | "." | ".." => format!("\"{key}\""),
+        _ => escape(key),
+    }
+}
+
+impl Kind {
+    pub fn of(value: &Value) -> Kind {
+        match value {
+            Value::Object(_) => Kind::Object,
+            Value::Array(_) => Kind::Array,
+            scalar => Kind::Scalar(scalar.to_string()),
+        }
+    }
+}
+
+impl Member {
+    pub fn is_scalar(&self) -> bool {
+        matches!(self.kind, Kind::Scalar(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = parse(r#"{"b": [1, "two"], "a": null}"#, Format::Json).unwrap();
+        let toml = parse("b = [1, \"two\"]\na = 1979-05-27", Format::Toml).unwrap();
+        let yaml = parse("b: [1, two]\n2: true", Format::Yaml).unwrap();
+        assert_eq!(json["b"], toml["b"]);
+        assert_eq!(json["b"], yaml["b"]);
+        // order of the keys is kept
+        assert_eq!(json.as_object().unwrap().keys().next().unwrap(), "b");
+        assert_eq!(toml["a"], "1979-05-27");
+        assert_eq!(yaml["2"], true);
+
+        assert_eq!(escape("a/b~c"), "a~1b~0c");
+        assert_eq!(name(".."), "\"..\"");
+        assert!(matches!(Kind::of(&json["b"][1]), Kind::Scalar(s) if s == "\"two\""));
+    }
+}
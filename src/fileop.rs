@@ -0,0 +1,462 @@
+use crate::trash;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// what to do when the destination already has an entry with
+/// the same name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conflict {
+    Ask,
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl FromStr for Conflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Conflict::Skip),
+            "overwrite" => Ok(Conflict::Overwrite),
+            "rename" => Ok(Conflict::Rename),
+            _ => Err(format!("unknown conflict resolution '{s}'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Copy,
+    Move,
+}
+
+impl Operation {
+    fn doing(&self) -> &'static str {
+        match self {
+            Operation::Copy => "copying",
+            Operation::Move => "moving",
+        }
+    }
+
+    fn done(&self) -> &'static str {
+        match self {
+            Operation::Copy => "copied",
+            Operation::Move => "moved",
+        }
+    }
+}
+
+/// entries of `sources` which name is already taken in `dest`
+pub fn conflicts(sources: &[PathBuf], dest: &Path) -> Vec<PathBuf> {
+    sources
+        .iter()
+        .filter_map(|it| it.file_name().map(|name| dest.join(name)))
+        .filter(|it| fs::symlink_metadata(it).is_ok())
+        .collect()
+}
+
+/// first "name (n).ext" which does not exist in `dest`
+fn free_name(dest: &Path, name: &Path) -> PathBuf {
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = name
+        .extension()
+        .map(|it| format!(".{}", it.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| dest.join(format!("{stem} ({n}){ext}")))
+        .find(|it| fs::symlink_metadata(it).is_err())
+        .unwrap()
+}
+
+/// number of entries (including itself) under `path`, links are
+/// not followed
+fn count(path: &Path) -> usize {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => {
+            1 + path.read_dir().map_or(0, |ents| {
+                ents.filter_map(Result::ok)
+                    .map(|ent| count(&ent.path()))
+                    .sum()
+            })
+        }
+        _ => 1,
+    }
+}
+
+pub fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    done: usize,
+    total: usize,
+    current: Option<PathBuf>,
+    errors: Vec<String>,
+    /// sources and where they ended up
+    pairs: Vec<(PathBuf, PathBuf)>,
+    /// overwritten nodes, and their names within the trash
+    trashed: Vec<(PathBuf, String)>,
+    count: usize,
+    skipped: usize,
+    finished: bool,
+}
+
+// counted in entries (see `count`), whether copying or moving
+impl Progress {
+    fn step(&mut self, path: &Path) {
+        self.step_by(path, 1);
+    }
+
+    fn step_by(&mut self, path: &Path, entries: usize) {
+        self.done += entries;
+        self.current = Some(path.to_path_buf());
+    }
+}
+
+fn copy_rec(from: &Path, to: &Path, progress: &Mutex<Progress>) -> io::Result<()> {
+    progress.lock().unwrap().step(from);
+    let meta = fs::symlink_metadata(from)?;
+
+    if meta.is_symlink() {
+        let target = fs::read_link(from)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, to)?;
+        #[cfg(windows)]
+        if target.is_dir() {
+            std::os::windows::fs::symlink_dir(target, to)?;
+        } else {
+            std::os::windows::fs::symlink_file(target, to)?;
+        }
+    } else if meta.is_dir() {
+        fs::create_dir(to)?;
+        for ent in from.read_dir()? {
+            let ent = ent?;
+            if let Err(err) = copy_rec(&ent.path(), &to.join(ent.file_name()), progress) {
+                // keep going with the rest of the directory
+                progress
+                    .lock()
+                    .unwrap()
+                    .errors
+                    .push(format!("{}: {err}", ent.path().to_string_lossy()));
+            }
+        }
+        fs::set_permissions(to, meta.permissions())?;
+    } else {
+        fs::copy(from, to)?;
+    }
+
+    Ok(())
+}
+
+/// `entries` is the `count` of `from`, for the progress
+fn move_one(from: &Path, to: &Path, entries: usize, progress: &Mutex<Progress>) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => {
+            progress.lock().unwrap().step_by(from, entries);
+            Ok(())
+        }
+        Err(err) if io::ErrorKind::CrossesDevices == err.kind() => {
+            let errors = progress.lock().unwrap().errors.len();
+            let copied = copy_rec(from, to, progress);
+            // only remove the source if every thing was copied,
+            // else the partial copy (the source is still whole)
+            if copied.is_ok() && errors == progress.lock().unwrap().errors.len() {
+                remove(from)
+            } else {
+                let _ = remove(to);
+                copied
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// rename, or copy then remove when crossing file systems
+pub fn relocate(from: &Path, to: &Path) -> io::Result<()> {
+    let progress = Mutex::new(Progress::default());
+    move_one(from, to, 1, &progress)?;
+    match progress.into_inner().unwrap().errors.first() {
+        Some(err) => Err(io::Error::other(err.clone())),
+        None => Ok(()),
+    }
+}
+
+/// recursive copy, without progress
+pub fn copy(from: &Path, to: &Path) -> io::Result<()> {
+    let progress = Mutex::new(Progress::default());
+    copy_rec(from, to, &progress)?;
+    match progress.into_inner().unwrap().errors.first() {
+        Some(err) => Err(io::Error::other(err.clone())),
+        None => Ok(()),
+    }
+}
+
+/// a new file in the temporary directory, private to the user; it
+/// cannot be something (eg. a link) that was already there
+pub fn temp_file(prefix: &str) -> io::Result<(PathBuf, fs::File)> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.subsec_nanos());
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    for k in 0..100 {
+        let path = env::temp_dir().join(format!("{prefix}-{}-{nanos}-{k}.txt", process::id()));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if io::ErrorKind::AlreadyExists == err.kind() => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::ErrorKind::AlreadyExists.into())
+}
+
+/// pair each of the `sources` with its new name (same directory),
+/// leaving out the ones that do not change; an error if names are
+/// invalid or if two would end up the same
+pub fn plan_renames(sources: &[PathBuf], names: &[&str]) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    if sources.len() != names.len() {
+        return Err(format!(
+            "expected {} names but got {} (lines cannot be added or removed)",
+            sources.len(),
+            names.len()
+        ));
+    }
+
+    let mut plan = Vec::new();
+    for (from, name) in sources.iter().zip(names) {
+        if name.is_empty() || "." == *name || ".." == *name || name.contains('/') {
+            return Err(format!("invalid name '{name}'"));
+        }
+        if from.file_name().is_some_and(|it| it == *name) {
+            continue;
+        }
+        let to = from.with_file_name(name);
+        if plan.iter().any(|(_, it)| *it == to) {
+            return Err(format!("'{}' would be the name of more than one node", to.to_string_lossy()));
+        }
+        plan.push((from.clone(), to));
+    }
+
+    // existing names are fine as long as they are renamed away (cycles
+    // like a->b, b->a are taken care of in `apply_renames`)
+    for (_, to) in &plan {
+        if fs::symlink_metadata(to).is_ok() && !plan.iter().any(|(from, _)| from == to) {
+            return Err(format!("'{}' already exists", to.to_string_lossy()));
+        }
+    }
+
+    Ok(plan)
+}
+
+/// rename everything to a temporary name first, then to its final
+/// name, so that chains and cycles work; @ret the renames that were
+/// done and the errors, if any
+pub fn apply_renames(plan: &[(PathBuf, PathBuf)]) -> (Vec<(PathBuf, PathBuf)>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut done = Vec::new();
+    let pid = std::process::id();
+
+    let moved = plan
+        .iter()
+        .enumerate()
+        .filter_map(|(k, (from, to))| {
+            // (`rename` would replace anything already named so)
+            let tmp = (0..)
+                .map(|n| from.with_file_name(format!(".treest-rename-{pid}-{k}-{n}")))
+                .find(|it| fs::symlink_metadata(it).is_err())
+                .unwrap();
+            match fs::rename(from, &tmp) {
+                Ok(()) => Some((from, tmp, to)),
+                Err(err) => {
+                    errors.push(format!("{}: {err}", from.to_string_lossy()));
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (from, tmp, to) in moved {
+        match fs::rename(&tmp, to) {
+            Ok(()) => done.push((from.clone(), to.clone())),
+            Err(err) => {
+                // try to at least put it back, unless something else
+                // was moved there since; then it stays where it is
+                if fs::symlink_metadata(from).is_err() && fs::rename(&tmp, from).is_ok() {
+                    errors.push(format!("{}: {err}", to.to_string_lossy()));
+                } else {
+                    errors.push(format!(
+                        "{}: {err} (left as {})",
+                        to.to_string_lossy(),
+                        tmp.to_string_lossy()
+                    ));
+                }
+            }
+        }
+    }
+
+    (done, errors)
+}
+
+fn run(
+    op: Operation,
+    sources: Vec<PathBuf>,
+    dest: PathBuf,
+    conflict: Conflict,
+    progress: Arc<Mutex<Progress>>,
+) {
+    let counts = sources.iter().map(|it| count(it)).collect::<Vec<_>>();
+    progress.lock().unwrap().total = counts.iter().sum();
+
+    for (source, entries) in sources.into_iter().zip(counts) {
+        let res = (|| {
+            let Some(name) = source.file_name() else {
+                return Err(io::Error::other("cannot use the root of the file system"));
+            };
+            if dest.starts_with(&source) {
+                return Err(io::Error::other("cannot put a directory into itself"));
+            }
+
+            let mut target = dest.join(name);
+            if fs::symlink_metadata(&target).is_ok() {
+                match conflict {
+                    Conflict::Ask | Conflict::Skip => return Ok(false),
+                    Conflict::Rename => target = free_name(&dest, Path::new(name)),
+                    Conflict::Overwrite if source.starts_with(&target) => {
+                        return Err(io::Error::other("destination would overwrite the source"))
+                    }
+                    // (not removed for good, so it can be undone)
+                    Conflict::Overwrite => {
+                        let name = trash::put(&target)?;
+                        progress.lock().unwrap().trashed.push((target.clone(), name));
+                    }
+                }
+            }
+
+            match op {
+                Operation::Copy => copy_rec(&source, &target, &progress)?,
+                Operation::Move => move_one(&source, &target, entries, &progress)?,
+            }
+
+            progress.lock().unwrap().pairs.push((source.clone(), target));
+            Ok(true)
+        })();
+
+        let mut p = progress.lock().unwrap();
+        match res {
+            Ok(true) => p.count += 1,
+            Ok(false) => p.skipped += 1,
+            Err(err) => p
+                .errors
+                .push(format!("{}: {err}", source.to_string_lossy())),
+        }
+    }
+
+    progress.lock().unwrap().finished = true;
+}
+
+/// a copy/move running in the background
+pub struct Job {
+    op: Operation,
+    progress: Arc<Mutex<Progress>>,
+}
+
+/// what a job did once it is finished
+pub struct JobReport {
+    pub op: Operation,
+    pub summary: String,
+    pub errors: Vec<String>,
+    pub pairs: Vec<(PathBuf, PathBuf)>,
+    pub trashed: Vec<(PathBuf, String)>,
+}
+
+impl Job {
+    pub fn start(op: Operation, sources: Vec<PathBuf>, dest: PathBuf, conflict: Conflict) -> Job {
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let progress_clone = Arc::clone(&progress);
+        thread::spawn(move || run(op, sources, dest, conflict, progress_clone));
+        Job { op, progress }
+    }
+
+    /// status line text while the job is running
+    pub fn status(&self) -> String {
+        let p = self.progress.lock().unwrap();
+        format!(
+            "{} {}/{}{}",
+            self.op.doing(),
+            p.done,
+            p.total,
+            p.current
+                .as_ref()
+                .map(|it| format!(": {}", it.to_string_lossy()))
+                .unwrap_or_default()
+        )
+    }
+
+    /// `None` while the job is still running
+    pub fn report(&self) -> Option<JobReport> {
+        let mut p = self.progress.lock().unwrap();
+        if !p.finished {
+            return None;
+        }
+        Some(JobReport {
+            op: self.op,
+            summary: format!(
+                "{} {} node{}{}",
+                self.op.done(),
+                p.count,
+                if p.count != 1 { "s" } else { "" },
+                if 0 < p.skipped {
+                    format!(" ({} skipped)", p.skipped)
+                } else {
+                    String::new()
+                }
+            ),
+            errors: std::mem::take(&mut p.errors),
+            pairs: std::mem::take(&mut p.pairs),
+            trashed: std::mem::take(&mut p.trashed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::plan_renames;
+    use crate::testdir::TestDir;
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn test_plan_renames() {
+        let dir = TestDir::new("renames");
+        for name in ["a", "b", "c"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let sources = ["a", "b", "c"].map(|it| dir.join(it)).to_vec();
+        let pair = |from: &str, to: &str| (dir.join(from), dir.join(to));
+
+        // cycle and unchanged
+        assert_eq!(
+            plan_renames(&sources, &["b", "a", "c"]),
+            Ok(vec![pair("a", "b"), pair("b", "a")])
+        );
+        // onto a node that stays
+        assert!(plan_renames(&sources, &["c", "b", "c"]).is_err());
+        // twice the same name
+        assert!(plan_renames(&sources, &["d", "d", "c"]).is_err());
+        assert!(plan_renames(&sources, &["a/b", "b", "c"]).is_err());
+        assert!(plan_renames(&sources, &["a", "b"]).is_err());
+        assert_eq!(plan_renames(&[PathBuf::from("x")], &["x"]), Ok(vec![]));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::node::{Filtering, Node};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+const MAX_RESULTS: usize = 1000;
+
+/// score of `candidate` against `pattern`, `None` if it does not
+/// match at all (ie. `pattern` is not a subsequence); smart-case
+pub fn score(pattern: &str, candidate: &str) -> Option<i64> {
+    let sensitive = pattern.chars().any(char::is_uppercase);
+    let eq = |a: char, b: char| {
+        if sensitive {
+            a == b
+        } else {
+            a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    let pat = pattern.chars().collect::<Vec<_>>();
+    let name_start = candidate.rfind('/').map_or(0, |k| k + 1);
+
+    let mut pi = 0;
+    let mut score = 0;
+    let mut prev = '/';
+    let mut prev_matched = false;
+    for (k, ch) in candidate.char_indices() {
+        if pi == pat.len() {
+            break;
+        }
+        if eq(ch, pat[pi]) {
+            score += 1;
+            if prev_matched {
+                // consecutive
+                score += 4;
+            }
+            if matches!(prev, '/' | '_' | '-' | '.' | ' ')
+                || (prev.is_lowercase() && ch.is_uppercase())
+            {
+                // start of a word
+                score += 3;
+            }
+            if name_start <= k {
+                // within the file name itself
+                score += 2;
+            }
+            pi += 1;
+            prev_matched = true;
+        } else {
+            if 0 < pi {
+                // gap
+                score -= 1;
+            }
+            prev_matched = false;
+        }
+        prev = ch;
+    }
+
+    if pi == pat.len() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+fn walk(
+    root: PathBuf,
+    filters: Vec<Filtering>,
+    index: Arc<Mutex<Vec<String>>>,
+    done: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+) {
+    let mut stack = vec![root.clone()];
+    while let Some(dir) = stack.pop() {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(entries) = dir.read_dir() else { continue; };
+        let mut found = Vec::new();
+        for ent in entries.filter_map(Result::ok) {
+            let Ok(node) = ent.metadata().and_then(|meta| Node::new(ent.path(), meta)) else { continue; };
+            if filters.iter().any(|f| f.matches(&node)) {
+                continue;
+            }
+            // links are not followed so no cycle
+            if node.is_dir() {
+                stack.push(ent.path());
+            }
+            if let Ok(rel) = node.as_path().strip_prefix(&root) {
+                found.push(rel.to_string_lossy().to_string());
+            }
+        }
+        index.lock().unwrap().extend(found);
+    }
+    done.store(true, Ordering::Relaxed);
+}
+
+pub enum FuzzyOutcome {
+    Continue,
+    Cancel,
+    Accept(PathBuf),
+}
+
+pub struct Fuzzy {
+    index: Arc<Mutex<Vec<String>>>,
+    done: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+    query: String,
+    cursor: usize,
+    selected: usize,
+    /// score, length and index in the index, best first
+    results: Vec<(i64, usize, usize)>,
+    ranked_query: Option<String>,
+    ranked_len: usize,
+}
+
+impl Drop for Fuzzy {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Fuzzy {
+    /// start indexing every path under `root` in the background,
+    /// skipping anything that matches one of the `filters`
+    pub fn new(root: &Path, filters: Vec<Filtering>) -> Fuzzy {
+        let index = Arc::new(Mutex::new(Vec::new()));
+        let done = Arc::new(AtomicBool::new(false));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let (root, index_clone, done_clone, cancel_clone) = (
+            root.to_path_buf(),
+            Arc::clone(&index),
+            Arc::clone(&done),
+            Arc::clone(&cancel),
+        );
+        thread::spawn(move || walk(root, filters, index_clone, done_clone, cancel_clone));
+
+        Fuzzy {
+            index,
+            done,
+            cancel,
+            query: String::new(),
+            cursor: 0,
+            selected: 0,
+            results: Vec::new(),
+            ranked_query: None,
+            ranked_len: 0,
+        }
+    }
+
+    /// re-rank if the query changed, or only rank what the index
+    /// got since last time
+    pub fn update(&mut self) {
+        let index = self.index.lock().unwrap();
+        let fresh = self.ranked_query.as_deref() != Some(&self.query);
+        if !fresh && self.ranked_len == index.len() {
+            return;
+        }
+
+        let from = if fresh { 0 } else { self.ranked_len };
+        let mut found = index[from..]
+            .iter()
+            .enumerate()
+            .filter_map(|(k, path)| score(&self.query, path).map(|s| (s, path.len(), from + k)))
+            .collect::<Vec<_>>();
+        self.ranked_len = index.len();
+        drop(index);
+
+        // best score first, then shortest
+        let rank = |(ls, ll, _): &(i64, usize, usize), (rs, rl, _): &(i64, usize, usize)| {
+            rs.cmp(ls).then_with(|| ll.cmp(rl))
+        };
+        found.sort_by(rank);
+        found.truncate(MAX_RESULTS);
+
+        if fresh {
+            self.results = found;
+            self.selected = 0;
+            self.ranked_query = Some(self.query.clone());
+        } else {
+            let previous = std::mem::take(&mut self.results);
+            let (mut l, mut r) = (previous.into_iter().peekable(), found.into_iter().peekable());
+            while self.results.len() < MAX_RESULTS {
+                let next = match (l.peek(), r.peek()) {
+                    (Some(a), Some(b)) if rank(b, a).is_lt() => r.next(),
+                    (Some(_), _) => l.next(),
+                    (None, _) => r.next(),
+                };
+                let Some(next) = next else { break; };
+                self.results.push(next);
+            }
+        }
+        if self.results.len() <= self.selected {
+            self.selected = self.results.len().saturating_sub(1);
+        }
+    }
+
+    pub fn do_event(&mut self, event: &Event) -> FuzzyOutcome {
+        let Event::Key(key) = event else { return FuzzyOutcome::Continue; };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c' | 'g') if ctrl => return FuzzyOutcome::Cancel,
+            KeyCode::Esc => return FuzzyOutcome::Cancel,
+
+            KeyCode::Char('j' | 'm') if ctrl => return self.accept(),
+            KeyCode::Enter => return self.accept(),
+
+            KeyCode::Char('p' | 'k') if ctrl => self.select_up(),
+            KeyCode::Up | KeyCode::Tab => self.select_up(),
+            KeyCode::Char('n') if ctrl => self.select_down(),
+            KeyCode::Down | KeyCode::BackTab => self.select_down(),
+
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.query.chars().count(),
+            KeyCode::Char(_) if ctrl => (),
+
+            KeyCode::Char(c) => {
+                let at = self.byte_at_cursor();
+                self.query.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if 0 < self.cursor => {
+                self.cursor -= 1;
+                let at = self.byte_at_cursor();
+                self.query.remove(at);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right if self.cursor < self.query.chars().count() => self.cursor += 1,
+
+            _ => (),
+        }
+
+        FuzzyOutcome::Continue
+    }
+
+    fn byte_at_cursor(&self) -> usize {
+        self.query
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.query.len(), |(k, _)| k)
+    }
+
+    fn select_up(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    fn select_down(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn accept(&mut self) -> FuzzyOutcome {
+        self.update();
+        match self.results.get(self.selected) {
+            Some((_, _, k)) => FuzzyOutcome::Accept(self.index.lock().unwrap()[*k].clone().into()),
+            None => FuzzyOutcome::Cancel,
+        }
+    }
+
+    /// where the terminal cursor should be, relative to the area
+    pub fn cursor_shift(&self) -> u16 {
+        (1 + 2 + self.cursor) as u16
+    }
+
+    /// area occupied by the overlay within `main`
+    pub fn area(&self, main: Rect) -> Rect {
+        let h = std::cmp::max(3, main.height / 2);
+        Rect::new(main.x, main.y + main.height - h, main.width, h)
+    }
+}
+
+impl Widget for &Fuzzy {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let total = self.index.lock().unwrap().len();
+        let title = format!(
+            " {}/{}{} ",
+            self.results.len(),
+            total,
+            if self.done.load(Ordering::Relaxed) {
+                ""
+            } else {
+                " (indexing\u{2026})"
+            }
+        );
+        let surround = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray))
+            .title(title);
+        let inner = surround.inner(area);
+        surround.render(area, buf);
+        if inner.height < 1 {
+            return;
+        }
+
+        let prompt_y = inner.y + inner.height - 1;
+        buf.set_spans(
+            inner.x,
+            prompt_y,
+            &Spans::from(vec![
+                Span::styled("> ", Style::default().fg(Color::DarkGray)),
+                Span::raw(&self.query),
+            ]),
+            inner.width,
+        );
+
+        // results are listed bottom up, best one closest to the prompt
+        let room = (inner.height - 1) as usize;
+        let first = if self.selected < room {
+            0
+        } else {
+            self.selected + 1 - room
+        };
+        let index = self.index.lock().unwrap();
+        for (line, (k, (_, _, in_index))) in self
+            .results
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(room)
+            .enumerate()
+        {
+            let style = if k == self.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            buf.set_stringn(
+                inner.x,
+                prompt_y - 1 - line as u16,
+                &index[*in_index],
+                inner.width as usize,
+                style,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("xyz", "src/main.rs"), None);
+        assert!(score("", "src/main.rs").is_some());
+        assert!(score("main", "src/main.rs") > score("main", "src/my_a_in_n.rs"));
+        assert!(score("Main", "src/main.rs").is_none());
+    }
+
+    #[test]
+    fn test_update() {
+        let paths = ["src/main.rs", "a/b/mayin", "main", "src/man.rs", "xyz", "domain.rs"];
+        let mut fz = Fuzzy::new(Path::new("/nonexistent"), Vec::new());
+        fz.query = "main".to_string();
+        for path in paths {
+            fz.index.lock().unwrap().push(path.to_string());
+            fz.update();
+        }
+        let incremental = fz.results.clone();
+        fz.ranked_query = None;
+        fz.update();
+        assert_eq!(fz.results, incremental);
+        assert_eq!(fz.results[0].2, 2);
+    }
+}
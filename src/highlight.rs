@@ -0,0 +1,499 @@
+// a (very) simple syntax highlighter for the preview: this is not
+// a parser, just enough tokenizing to make most files readable
+use std::path::Path;
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Syntax {
+    Rust,
+    Toml,
+    Json,
+    Shell,
+    Markdown,
+}
+
+impl Syntax {
+    /// by extension (or well-known name), else by shebang
+    pub fn detect(path: &Path, first_line: &str) -> Option<Syntax> {
+        let name = path.file_name()?.to_str()?;
+        let by_name = match name {
+            "Cargo.lock" => Some(Syntax::Toml),
+            ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" | ".zprofile" => {
+                Some(Syntax::Shell)
+            }
+            _ => match path.extension().and_then(|it| it.to_str()) {
+                Some("rs") => Some(Syntax::Rust),
+                Some("toml") => Some(Syntax::Toml),
+                Some("json") => Some(Syntax::Json),
+                Some("sh" | "bash" | "zsh" | "ksh") => Some(Syntax::Shell),
+                Some("md" | "markdown") => Some(Syntax::Markdown),
+                _ => None,
+            },
+        };
+        by_name.or_else(|| {
+            let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+            let mut interp = words.next()?.rsplit('/').next()?;
+            if "env" == interp {
+                interp = words.find(|it| !it.starts_with('-'))?;
+            }
+            match interp {
+                "sh" | "bash" | "dash" | "zsh" | "ksh" | "ash" => Some(Syntax::Shell),
+                _ => None,
+            }
+        })
+    }
+
+    fn lang(self) -> &'static Lang {
+        match self {
+            Syntax::Rust => &RUST,
+            Syntax::Toml => &TOML,
+            Syntax::Json => &JSON,
+            Syntax::Shell => &SHELL,
+            Syntax::Markdown => unreachable!(),
+        }
+    }
+}
+
+struct Lang {
+    line_comment: &'static str,
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// quote of strings without escapes
+    raw_quote: Option<char>,
+    /// strings can span multiple lines
+    multiline: bool,
+    keywords: &'static [&'static str],
+    /// identifiers followed by this are keys (eg. `=` in TOML)
+    key_before: Option<char>,
+    /// `'a` is not always a character
+    lifetimes: bool,
+    /// `$name` or `${name}`
+    variables: bool,
+}
+
+const RUST: Lang = Lang {
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    raw_quote: None,
+    multiline: true,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    key_before: None,
+    lifetimes: true,
+    variables: false,
+};
+
+const TOML: Lang = Lang {
+    line_comment: "#",
+    block_comment: None,
+    quotes: &['"', '\''],
+    raw_quote: Some('\''),
+    multiline: true,
+    keywords: &["true", "false"],
+    key_before: Some('='),
+    lifetimes: false,
+    variables: false,
+};
+
+const JSON: Lang = Lang {
+    // (none in JSON)
+    line_comment: "",
+    block_comment: None,
+    quotes: &['"'],
+    raw_quote: None,
+    multiline: false,
+    keywords: &["true", "false", "null"],
+    key_before: Some(':'),
+    lifetimes: false,
+    variables: false,
+};
+
+const SHELL: Lang = Lang {
+    line_comment: "#",
+    block_comment: None,
+    quotes: &['"', '\'', '`'],
+    raw_quote: Some('\''),
+    multiline: true,
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    key_before: None,
+    lifetimes: false,
+    variables: true,
+};
+
+fn comment() -> Style {
+    Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC)
+}
+fn string() -> Style {
+    Style::default().fg(Color::Green)
+}
+fn keyword() -> Style {
+    Style::default().fg(Color::Magenta)
+}
+fn number() -> Style {
+    Style::default().fg(Color::Cyan)
+}
+fn key() -> Style {
+    Style::default().fg(Color::Blue)
+}
+
+/// what is still open at the end of a line
+#[derive(Clone, Copy, PartialEq)]
+enum Carry {
+    None,
+    Comment,
+    Str(char),
+    Fence,
+}
+
+#[derive(Default)]
+struct Out(Vec<Span<'static>>);
+
+impl Out {
+    fn push(&mut self, text: &[char], style: Style) {
+        if text.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().extend(text),
+            _ => self
+                .0
+                .push(Span::styled(text.iter().collect::<String>(), style)),
+        }
+    }
+}
+
+fn starts_with(chs: &[char], pat: &str) -> bool {
+    !pat.is_empty()
+        && pat.chars().count() <= chs.len()
+        && pat.chars().zip(chs).all(|(a, b)| a == *b)
+}
+
+fn find(chs: &[char], pat: &str) -> Option<usize> {
+    (0..chs.len()).find(|k| starts_with(&chs[*k..], pat))
+}
+
+/// end of the string starting after its opening quote, and whether
+/// it was closed on this line
+fn scan_string(lang: &Lang, chs: &[char], q: char) -> (usize, bool) {
+    let mut k = 0;
+    while k < chs.len() {
+        match chs[k] {
+            '\\' if Some(q) != lang.raw_quote => k += 2,
+            c if c == q => return (k + 1, true),
+            _ => k += 1,
+        }
+    }
+    (chs.len(), false)
+}
+
+fn next_non_space(chs: &[char]) -> Option<char> {
+    chs.iter().copied().find(|c| !c.is_whitespace())
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || '_' == c
+}
+
+fn code_line(lang: &Lang, chs: &[char], carry: &mut Carry) -> Out {
+    let mut out = Out::default();
+
+    // TOML tables
+    if lang.key_before == Some('=') && Carry::None == *carry && Some('[') == next_non_space(chs) {
+        let end = find(chs, lang.line_comment).unwrap_or(chs.len());
+        out.push(&chs[..end], key().add_modifier(Modifier::BOLD));
+        out.push(&chs[end..], comment());
+        return out;
+    }
+
+    let mut k = 0;
+    while k < chs.len() {
+        let rest = &chs[k..];
+        match *carry {
+            Carry::Comment => {
+                let (_, close) = lang.block_comment.unwrap();
+                let end = find(rest, close).map_or(rest.len(), |e| {
+                    *carry = Carry::None;
+                    e + close.len()
+                });
+                out.push(&rest[..end], comment());
+                k += end;
+            }
+
+            Carry::Str(q) => {
+                let (end, closed) = scan_string(lang, rest, q);
+                if closed {
+                    *carry = Carry::None;
+                }
+                out.push(&rest[..end.min(rest.len())], string());
+                k += end;
+            }
+
+            _ => {
+                let c = rest[0];
+                if starts_with(rest, lang.line_comment) {
+                    out.push(rest, comment());
+                    break;
+                }
+                if let Some((open, _)) = lang
+                    .block_comment
+                    .filter(|(open, _)| starts_with(rest, open))
+                {
+                    *carry = Carry::Comment;
+                    out.push(&rest[..open.len()], comment());
+                    k += open.len();
+                } else if '\'' == c
+                    && lang.lifetimes
+                    && Some(&'\\') != rest.get(1)
+                    && Some(&'\'') != rest.get(2)
+                {
+                    // a lifetime (or label) rather than a character
+                    let end = 1 + rest[1..].iter().take_while(|c| is_ident(**c)).count();
+                    out.push(&rest[..end], number());
+                    k += end;
+                } else if lang.quotes.contains(&c) {
+                    let (end, closed) = scan_string(lang, &rest[1..], c);
+                    let end = 1 + end;
+                    if !closed && lang.multiline {
+                        *carry = Carry::Str(c);
+                    }
+                    let is_key = closed
+                        && lang.key_before.is_some()
+                        && lang.key_before == next_non_space(&rest[end..]);
+                    out.push(&rest[..end], if is_key { key() } else { string() });
+                    k += end;
+                } else if c.is_ascii_digit()
+                    || ('-' == c && rest.get(1).is_some_and(char::is_ascii_digit))
+                {
+                    let end = 1 + rest[1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_'))
+                        .count();
+                    out.push(&rest[..end], number());
+                    k += end;
+                } else if '$' == c && lang.variables {
+                    let end = match rest.get(1) {
+                        Some('{') => rest
+                            .iter()
+                            .position(|c| '}' == *c)
+                            .map_or(rest.len(), |e| e + 1),
+                        Some(c) if is_ident(*c) => {
+                            1 + rest[1..].iter().take_while(|c| is_ident(**c)).count()
+                        }
+                        Some(_) => 2,
+                        None => 1,
+                    };
+                    out.push(&rest[..end], number());
+                    k += end;
+                } else if is_ident(c) {
+                    let end = rest
+                        .iter()
+                        .take_while(|c| {
+                            is_ident(**c) || ('-' == **c && lang.key_before == Some('='))
+                        })
+                        .count();
+                    let word = rest[..end].iter().collect::<String>();
+                    let style = if lang.keywords.contains(&word.as_str()) {
+                        keyword()
+                    } else if lang.key_before.is_some()
+                        && lang.key_before == next_non_space(&rest[end..])
+                    {
+                        key()
+                    } else {
+                        Style::default()
+                    };
+                    out.push(&rest[..end], style);
+                    k += end;
+                } else {
+                    out.push(&rest[..1], Style::default());
+                    k += 1;
+                }
+            }
+        }
+    }
+
+    if let Carry::Str(_) = carry {
+        if !lang.multiline {
+            *carry = Carry::None;
+        }
+    }
+    out
+}
+
+fn markdown_inline(chs: &[char], out: &mut Out, base: Style) {
+    let mut k = 0;
+    while k < chs.len() {
+        let rest = &chs[k..];
+        let closing = |pat: &str| find(&rest[pat.len()..], pat).map(|e| e + 2 * pat.len());
+        if let Some(end) = starts_with(rest, "`").then(|| closing("`")).flatten() {
+            out.push(&rest[..end], string());
+            k += end;
+        } else if let Some(end) = starts_with(rest, "**").then(|| closing("**")).flatten() {
+            out.push(&rest[..end], base.add_modifier(Modifier::BOLD));
+            k += end;
+        } else if let Some(end) = (starts_with(rest, "*") || starts_with(rest, "_"))
+            .then(|| closing(&rest[0].to_string()))
+            .flatten()
+            .filter(|end| 2 < *end)
+        {
+            out.push(&rest[..end], base.add_modifier(Modifier::ITALIC));
+            k += end;
+        } else if let Some((text, link)) = starts_with(rest, "[")
+            .then(|| {
+                let text = find(rest, "](")? + 1;
+                let link = find(&rest[text..], ")")? + 1;
+                Some((text, text + link))
+            })
+            .flatten()
+        {
+            out.push(&rest[..text], key().add_modifier(Modifier::UNDERLINED));
+            out.push(&rest[text..link], comment());
+            k += link;
+        } else {
+            out.push(&rest[..1], base);
+            k += 1;
+        }
+    }
+}
+
+fn markdown_line(chs: &[char], carry: &mut Carry) -> Out {
+    let mut out = Out::default();
+    let trimmed = chs
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(chs.len());
+    let rest = &chs[trimmed..];
+
+    if starts_with(rest, "```") || starts_with(rest, "~~~") {
+        *carry = if Carry::Fence == *carry {
+            Carry::None
+        } else {
+            Carry::Fence
+        };
+        out.push(chs, comment());
+    } else if Carry::Fence == *carry {
+        out.push(chs, string());
+    } else if starts_with(rest, "#") {
+        out.push(
+            chs,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    } else if starts_with(rest, ">") {
+        out.push(&chs[..trimmed + 1], comment());
+        markdown_inline(
+            &rest[1..],
+            &mut out,
+            Style::default().add_modifier(Modifier::ITALIC),
+        );
+    } else {
+        let marker = if ["- ", "* ", "+ "].iter().any(|m| starts_with(rest, m)) {
+            2
+        } else {
+            let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+            if 0 < digits && starts_with(&rest[digits..], ". ") {
+                digits + 2
+            } else {
+                0
+            }
+        };
+        out.push(&chs[..trimmed], Style::default());
+        out.push(&rest[..marker], number());
+        markdown_inline(&rest[marker..], &mut out, Style::default());
+    }
+    out
+}
+
+/// every lines of `text` as styled spans; `sanitize` is applied to
+/// each lines first (it should not change their meaning)
+pub fn highlight(
+    syntax: Syntax,
+    text: &str,
+    sanitize: impl Fn(&str) -> String,
+) -> Vec<Spans<'static>> {
+    let mut carry = Carry::None;
+    text.lines()
+        .map(|line| {
+            let chs = sanitize(line).chars().collect::<Vec<_>>();
+            let out = match syntax {
+                Syntax::Markdown => markdown_line(&chs, &mut carry),
+                _ => code_line(syntax.lang(), &chs, &mut carry),
+            };
+            Spans::from(out.0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{highlight, Syntax};
+    use std::path::Path;
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            Syntax::detect(Path::new("a/main.rs"), ""),
+            Some(Syntax::Rust)
+        );
+        assert_eq!(
+            Syntax::detect(Path::new("Cargo.lock"), ""),
+            Some(Syntax::Toml)
+        );
+        assert_eq!(
+            Syntax::detect(Path::new("run"), "#!/usr/bin/env -S bash -e"),
+            Some(Syntax::Shell)
+        );
+        assert_eq!(Syntax::detect(Path::new("run"), "#!/usr/bin/python3"), None);
+
+        let words = |syntax, text| {
+            highlight(syntax, text, str::to_string)
+                .into_iter()
+                .map(|line| {
+                    line.0
+                        .into_iter()
+                        .map(|span| span.content.to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            words(
+                Syntax::Rust,
+                "fn f<'a>(s: &'a str) /* x\ny */ -> char { '\\'' }"
+            ),
+            [
+                vec!["fn", " f<", "'a", ">(s: &", "'a", " str) ", "/* x"],
+                vec!["y */", " -> char { ", "'\\''", " }"],
+            ]
+        );
+        assert_eq!(
+            words(Syntax::Json, r#"{"a": "b", "c": [1, true]}"#),
+            [vec![
+                "{", "\"a\"", ": ", "\"b\"", ", ", "\"c\"", ": [", "1", ", ", "true", "]}"
+            ]]
+        );
+        assert_eq!(
+            words(Syntax::Markdown, "# t\n```\nx\n```\n- a `b`"),
+            [
+                vec!["# t"],
+                vec!["```"],
+                vec!["x"],
+                vec!["```"],
+                vec!["- ", "a ", "`b`"]
+            ]
+        );
+    }
+}
@@ -0,0 +1,367 @@
+// everything that can be said about a node, for the `info` command
+use crate::{
+    magic,
+    node::{mode_to_string, size_to_string, time_to_string, SizeFormat, TimeFormat},
+    source::{EntryKind, Meta, TreeSource},
+};
+use std::{
+    fs::{self, Metadata},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+fn time(time: Option<SystemTime>) -> String {
+    match time {
+        Some(time) => format!(
+            "{} ({})",
+            time_to_string(time, TimeFormat::Iso),
+            time_to_string(time, TimeFormat::Relative)
+        ),
+        None => "-".to_string(),
+    }
+}
+
+/// every links followed from `path`, up to the final target
+fn link_chain(source: &dyn TreeSource, path: &Path) -> String {
+    let mut chain = vec![path.to_string_lossy().to_string()];
+    let mut at = path.to_path_buf();
+    // same limit as the kernel's
+    for _ in 0..40 {
+        let target = match source.read_link(&at) {
+            Ok(target) => target,
+            Err(_) => return chain.join(" -> "),
+        };
+        at = at.parent().map_or(target.clone(), |par| par.join(&target));
+        chain.push(target.to_string_lossy().to_string());
+        if source.symlink_metadata(&at).is_err() {
+            chain.push("(broken)".to_string());
+            return chain.join(" -> ");
+        }
+    }
+    chain.push("(too many levels)".to_string());
+    chain.join(" -> ")
+}
+
+/// count of the entries of a directory, by kind
+fn entry_counts(source: &dyn TreeSource, path: &Path) -> io::Result<String> {
+    let (mut dirs, mut files, mut links, mut hidden, mut others) = (0, 0, 0, 0, 0);
+    for (path, meta) in source.read_dir(path)? {
+        match meta.kind {
+            EntryKind::Dir => dirs += 1,
+            EntryKind::File => files += 1,
+            EntryKind::Symlink => links += 1,
+            _ => others += 1,
+        }
+        if path
+            .file_name()
+            .is_some_and(|it| it.to_string_lossy().starts_with('.'))
+        {
+            hidden += 1;
+        }
+    }
+    Ok(format!(
+        "{} ({dirs} directories, {files} files, {links} links, {others} others; {hidden} hidden)",
+        dirs + files + links + others
+    ))
+}
+
+#[cfg(unix)]
+fn platform_details(meta: &Metadata, out: &mut Vec<(&'static str, String)>) {
+    use crate::perms::{group_name, user_name};
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = meta.dev();
+    // glibc's `gnu_dev_major` and `gnu_dev_minor`
+    let major = (dev >> 32 & 0xfffff000) | (dev >> 8 & 0xfff);
+    let minor = (dev >> 12 & 0xffffff00) | (dev & 0xff);
+    let (uid, gid) = (meta.uid(), meta.gid());
+
+    out.extend([
+        ("inode", meta.ino().to_string()),
+        ("device", format!("{dev:#x} ({major}:{minor})")),
+        ("links", meta.nlink().to_string()),
+        (
+            "owner",
+            format!("{} ({uid})", user_name(uid).unwrap_or_default()),
+        ),
+        (
+            "group",
+            format!("{} ({gid})", group_name(gid).unwrap_or_default()),
+        ),
+        (
+            "mode",
+            format!(
+                "{} ({:04o})",
+                mode_to_string(meta.mode()),
+                meta.mode() & 0o7777
+            ),
+        ),
+        ("accessed", time(meta.accessed().ok())),
+        ("modified", time(meta.modified().ok())),
+        (
+            "changed",
+            time(Some(SystemTime::UNIX_EPOCH
+                + std::time::Duration::new(meta.ctime().max(0) as u64, meta.ctime_nsec() as u32))),
+        ),
+    ]);
+}
+
+#[cfg(windows)]
+fn platform_details(meta: &Metadata, out: &mut Vec<(&'static str, String)>) {
+    out.extend([
+        ("readonly", meta.permissions().readonly().to_string()),
+        ("accessed", time(meta.accessed().ok())),
+        ("modified", time(meta.modified().ok())),
+        ("created", time(meta.created().ok())),
+    ]);
+}
+
+/// what any source knows, when not on the local file system
+fn details(meta: &Meta, out: &mut Vec<(&'static str, String)>) {
+    out.extend([
+        (
+            "mode",
+            format!("{} ({:04o})", mode_to_string(meta.mode), meta.mode),
+        ),
+        ("accessed", time(meta.accessed)),
+        ("modified", time(meta.modified)),
+        ("created", time(meta.created)),
+    ]);
+}
+
+/// printable ASCII as is (a backslash doubled), any other byte as
+/// `\xNN`; names and values are whatever was set, not necessarily text
+#[cfg(any(test, target_os = "linux"))]
+fn escaped(bytes: &[u8]) -> String {
+    let mut r = String::with_capacity(bytes.len());
+    for b in bytes {
+        match b {
+            b'\\' => r.push_str("\\\\"),
+            b' '..=b'~' => r.push(*b as char),
+            _ => r.push_str(&format!("\\x{b:02x}")),
+        }
+    }
+    r
+}
+
+#[cfg(target_os = "linux")]
+fn xattrs(path: &Path) -> io::Result<Vec<String>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let cpath = CString::new(path.as_os_str().as_bytes()).map_err(io::Error::other)?;
+    // asked for the size first (with an empty buffer), then filled
+    let sized = |get: &dyn Fn(*mut libc::c_void, usize) -> isize| {
+        let len = get(std::ptr::null_mut(), 0);
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut buf = vec![0u8; len as usize];
+        let len = get(buf.as_mut_ptr().cast(), buf.len());
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        buf.truncate(len as usize);
+        Ok(buf)
+    };
+
+    // SAFETY: both strings are nul-terminated and outlive the calls, and
+    // `size` is the length of `buf` (or 0 with a null `buf`, to get the size)
+    let names = sized(&|buf, size| unsafe { libc::llistxattr(cpath.as_ptr(), buf.cast(), size) })?;
+    Ok(names
+        .split(|b| 0 == *b)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let cname = CString::new(name).unwrap();
+            let value = sized(&|buf, size| unsafe {
+                libc::lgetxattr(cpath.as_ptr(), cname.as_ptr(), buf, size)
+            });
+            let name = escaped(name);
+            match value {
+                Ok(value) => format!("{name}={}", escaped(&value)),
+                Err(err) => format!("{name} ({err})"),
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn xattrs(_path: &Path) -> io::Result<Vec<String>> {
+    Err(io::Error::other("not supported on this platform"))
+}
+
+/// labeled lines describing the node at `path` in `source`; inode,
+/// owner, extended attributes and such only for the local file system
+pub fn describe(source: &dyn TreeSource, path: &Path) -> io::Result<Vec<(&'static str, String)>> {
+    let meta = source.symlink_metadata(path)?;
+    let mut out = vec![
+        ("path", path.to_string_lossy().to_string()),
+        (
+            "canonical",
+            source.canonicalize(path).map_or_else(
+                |err| format!("({err})"),
+                |it: PathBuf| it.to_string_lossy().to_string(),
+            ),
+        ),
+    ];
+
+    if meta.is_symlink() {
+        out.push(("link", link_chain(source, path)));
+    }
+
+    out.push((
+        "type",
+        match meta.kind {
+            EntryKind::Dir => "directory",
+            EntryKind::Symlink => "symbolic link",
+            EntryKind::File => "regular file",
+            _ => "special file",
+        }
+        .to_string(),
+    ));
+    out.push((
+        "size",
+        format!(
+            "{} ({} bytes)",
+            size_to_string(meta.len, SizeFormat::Iec),
+            meta.len
+        ),
+    ));
+
+    match fs::symlink_metadata(path) {
+        Ok(meta) if source.is_local() => {
+            platform_details(&meta, &mut out);
+            match xattrs(path) {
+                Ok(list) if list.is_empty() => (),
+                Ok(list) => out.push(("xattrs", list.join(", "))),
+                Err(err) => out.push(("xattrs", format!("({err})"))),
+            }
+        }
+        _ => details(&meta, &mut out),
+    }
+
+    match source.metadata(path) {
+        Ok(meta) if meta.is_dir() => out.push((
+            "entries",
+            entry_counts(source, path).unwrap_or_else(|err| format!("({err})")),
+        )),
+        Ok(meta) if meta.is_file() => out.push((
+            "mime",
+            magic::detect(source, path).map_or_else(
+                |err| format!("({err})"),
+                |it| format!("{} ({})", it.mime, it.kind),
+            ),
+        )),
+        _ => (),
+    }
+
+    Ok(out)
+}
+
+/// the description of a node, over the views until a key is pressed
+pub struct Popup {
+    title: String,
+    lines: Vec<(&'static str, String)>,
+}
+
+impl Popup {
+    pub fn new(path: &Path, lines: Vec<(&'static str, String)>) -> Popup {
+        let title = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |it| it.to_string_lossy());
+        Popup {
+            title: format!(" {title} "),
+            lines,
+        }
+    }
+
+    fn label_width(&self) -> usize {
+        self.lines.iter().map(|(label, _)| label.len()).max().unwrap_or(0)
+    }
+
+    /// centered within `main`, as big as needed (and possible)
+    pub fn area(&self, main: Rect) -> Rect {
+        let value_width = self
+            .lines
+            .iter()
+            .map(|(_, value)| value.chars().count())
+            .max()
+            .unwrap_or(0);
+        let w = (self.label_width() + 2 + value_width + 2).min(main.width as usize) as u16;
+        let h = (self.lines.len() + 2).min(main.height as usize) as u16;
+        Rect::new(
+            main.x + (main.width - w) / 2,
+            main.y + (main.height - h) / 2,
+            w,
+            h,
+        )
+    }
+}
+
+impl Widget for &Popup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let surround = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray))
+            .title(self.title.as_str());
+        let inner = surround.inner(area);
+        surround.render(area, buf);
+
+        let width = self.label_width();
+        let dim = Style::default().fg(Color::DarkGray);
+        for (k, (label, value)) in self.lines.iter().take(inner.height as usize).enumerate() {
+            buf.set_spans(
+                inner.x,
+                inner.y + k as u16,
+                &Spans::from(vec![
+                    Span::styled(format!("{label:>width$}  "), dim),
+                    Span::raw(value.as_str()),
+                ]),
+                inner.width,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Memory;
+
+    #[test]
+    fn test_escaped() {
+        assert_eq!(escaped(b"user.a b"), "user.a b");
+        assert_eq!(escaped(b"\x1b[2J\\\xff\0"), "\\x1b[2J\\\\\\xff\\x00");
+        assert_eq!(escaped("\u{e9}".as_bytes()), "\\xc3\\xa9");
+    }
+
+    #[test]
+    fn test_describe() {
+        let mem = Memory::default();
+        mem.text("/r/a", "{}");
+        mem.link("/r/b", "a");
+        let lines = describe(&mem, Path::new("/r/b")).unwrap();
+        let get = |label| {
+            lines
+                .iter()
+                .find(|(it, _)| label == *it)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("canonical"), Some("/r/a"));
+        assert_eq!(get("link"), Some("/r/b -> a"));
+        assert_eq!(get("type"), Some("symbolic link"));
+        assert_eq!(get("mime"), Some("text/plain (text)"));
+        // nothing from whatever is at /r/b locally
+        assert_eq!(get("inode"), None);
+        assert_eq!(get("mode"), Some("rw-r--r-- (0644)"));
+    }
+}
@@ -0,0 +1,389 @@
+use crate::{
+    fileop,
+    node::{time_to_string, TimeFormat},
+    trash,
+};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// a file operation, as it happened
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Record {
+    Copy(Vec<(PathBuf, PathBuf)>),
+    Move(Vec<(PathBuf, PathBuf)>),
+    Rename(Vec<(PathBuf, PathBuf)>),
+    /// original paths and names within the trash
+    Trash(Vec<(PathBuf, String)>),
+    Restore(Vec<(PathBuf, String)>),
+    Delete(Vec<PathBuf>),
+}
+
+fn plural(n: usize) -> &'static str {
+    if n != 1 {
+        "s"
+    } else {
+        ""
+    }
+}
+
+/// the first few of `items`, then how many more there are
+fn truncated(items: Vec<String>) -> String {
+    const SHOWN: usize = 3;
+    if items.len() <= SHOWN {
+        items.join(", ")
+    } else {
+        format!("{}, and {} more", items[..SHOWN].join(", "), items.len() - SHOWN)
+    }
+}
+
+fn errors_of<T>(it: impl Iterator<Item = (PathBuf, io::Result<T>)>) -> (Vec<T>, Vec<String>) {
+    let mut done = Vec::new();
+    let mut errors = Vec::new();
+    for (path, res) in it {
+        match res {
+            Ok(r) => done.push(r),
+            Err(err) => errors.push(format!("{}: {err}", path.to_string_lossy())),
+        }
+    }
+    (done, errors)
+}
+
+fn move_back(pairs: &[(PathBuf, PathBuf)]) -> (Vec<(PathBuf, PathBuf)>, Vec<String>) {
+    errors_of(pairs.iter().map(|(from, to)| {
+        let res = if fs::symlink_metadata(from).is_ok() {
+            Err(io::Error::other("already exists"))
+        } else {
+            fileop::relocate(to, from).map(|()| (from.clone(), to.clone()))
+        };
+        (from.clone(), res)
+    }))
+}
+
+fn restore(entries: &[(PathBuf, String)]) -> (Vec<(PathBuf, String)>, Vec<String>) {
+    errors_of(entries.iter().map(|(path, name)| {
+        let entry = trash::Entry {
+            name: name.clone(),
+            path: path.clone(),
+            date: String::new(),
+        };
+        (path.clone(), trash::restore(&entry).map(|()| (path.clone(), name.clone())))
+    }))
+}
+
+fn put(entries: &[(PathBuf, String)]) -> (Vec<(PathBuf, String)>, Vec<String>) {
+    errors_of(
+        entries
+            .iter()
+            .map(|(path, _)| (path.clone(), trash::put(path).map(|name| (path.clone(), name)))),
+    )
+}
+
+impl Record {
+    pub fn describe(&self) -> String {
+        let pairs = |verb: &str, v: &[(PathBuf, PathBuf)]| {
+            format!(
+                "{verb} {} node{}: {}",
+                v.len(),
+                plural(v.len()),
+                truncated(
+                    v.iter()
+                        .map(|(from, to)| {
+                            format!("{} -> {}", from.to_string_lossy(), to.to_string_lossy())
+                        })
+                        .collect()
+                )
+            )
+        };
+        let paths = |verb: &str, v: &mut dyn Iterator<Item = &PathBuf>| {
+            let v = v.map(|it| it.to_string_lossy().to_string()).collect::<Vec<_>>();
+            format!("{verb} {} node{}: {}", v.len(), plural(v.len()), truncated(v))
+        };
+        match self {
+            Record::Copy(v) => pairs("copy", v),
+            Record::Move(v) => pairs("move", v),
+            Record::Rename(v) => pairs("rename", v),
+            Record::Trash(v) => paths("trash", &mut v.iter().map(|(it, _)| it)),
+            Record::Restore(v) => paths("restore", &mut v.iter().map(|(it, _)| it)),
+            Record::Delete(v) => paths("delete", &mut v.iter()),
+        }
+    }
+
+    /// every paths involved, to update the tree with
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            Record::Copy(v) | Record::Move(v) | Record::Rename(v) => v
+                .iter()
+                .flat_map(|(from, to)| [from.clone(), to.clone()])
+                .collect(),
+            Record::Trash(v) | Record::Restore(v) => v.iter().map(|(it, _)| it.clone()).collect(),
+            Record::Delete(v) => v.clone(),
+        }
+    }
+
+    /// reverse the operation where possible; @ret what can be
+    /// redone (if anything) and the errors
+    fn undo(&self) -> (Option<Record>, Vec<String>) {
+        match self {
+            Record::Copy(v) => {
+                // copies are not removed for good, just in case
+                let (done, errors) = errors_of(
+                    v.iter()
+                        .map(|(from, to)| (to.clone(), trash::put(to).map(|_| (from.clone(), to.clone())))),
+                );
+                (Some(Record::Copy(done)), errors)
+            }
+            Record::Move(v) => {
+                let (done, errors) = move_back(v);
+                (Some(Record::Move(done)), errors)
+            }
+            Record::Rename(v) => {
+                let back = v.iter().map(|(from, to)| (to.clone(), from.clone())).collect::<Vec<_>>();
+                let (done, errors) = fileop::apply_renames(&back);
+                let done = done.into_iter().map(|(to, from)| (from, to)).collect();
+                (Some(Record::Rename(done)), errors)
+            }
+            Record::Trash(v) => {
+                let (done, errors) = restore(v);
+                (Some(Record::Trash(done)), errors)
+            }
+            Record::Restore(v) => {
+                let (done, errors) = put(v);
+                (Some(Record::Restore(done)), errors)
+            }
+            Record::Delete(_) => (
+                None,
+                vec!["nodes deleted permanently cannot be brought back".to_string()],
+            ),
+        }
+    }
+
+    /// apply the operation again after it was undone
+    fn redo(&self) -> (Option<Record>, Vec<String>) {
+        match self {
+            Record::Copy(v) => {
+                let (done, errors) = errors_of(v.iter().map(|(from, to)| {
+                    (from.clone(), fileop::copy(from, to).map(|()| (from.clone(), to.clone())))
+                }));
+                (Some(Record::Copy(done)), errors)
+            }
+            Record::Move(v) => {
+                let (done, errors) = errors_of(v.iter().map(|(from, to)| {
+                    (from.clone(), fileop::relocate(from, to).map(|()| (from.clone(), to.clone())))
+                }));
+                (Some(Record::Move(done)), errors)
+            }
+            Record::Rename(v) => {
+                let (done, errors) = fileop::apply_renames(v);
+                (Some(Record::Rename(done)), errors)
+            }
+            Record::Trash(v) => {
+                let (done, errors) = put(v);
+                (Some(Record::Trash(done)), errors)
+            }
+            Record::Restore(v) => {
+                let (done, errors) = restore(v);
+                (Some(Record::Restore(done)), errors)
+            }
+            Record::Delete(_) => (None, Vec::new()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    date: &'a str,
+    what: &'a str,
+    record: &'a Record,
+}
+
+/// the file operations of the session, that can be undone/redone;
+/// everything is also appended to a log file in the cache dir (only
+/// the last `LOG_LEN` lines are kept), as a record of what happened
+#[derive(Default)]
+pub struct Journal {
+    done: Vec<(String, Record)>,
+    undone: Vec<(String, Record)>,
+    log: Option<PathBuf>,
+}
+
+const JOURNAL_LEN: usize = 100;
+const LOG_LEN: usize = 1000;
+
+impl Journal {
+    fn get_log_path() -> Option<PathBuf> {
+        let mut acc = home_dir()?;
+        acc.push(".cache");
+        acc.push("treest");
+        acc.push("journal.jsonl");
+        Some(acc)
+    }
+
+    pub fn new() -> Journal {
+        Journal::with_log(Journal::get_log_path())
+    }
+
+    /// start empty, logging to `log` (trimmed to its last `LOG_LEN` lines)
+    fn with_log(log: Option<PathBuf>) -> Journal {
+        if let Some(path) = &log {
+            if let Ok(content) = fs::read_to_string(path) {
+                let lines = content.lines().collect::<Vec<_>>();
+                if LOG_LEN < lines.len() {
+                    let _ = fs::write(path, lines[lines.len() - LOG_LEN..].join("\n") + "\n");
+                }
+            }
+        }
+        Journal {
+            log,
+            ..Journal::default()
+        }
+    }
+
+    // the record logged is the one that would be pushed (eg. for an
+    // "undo", what can be redone)
+    fn log(&self, what: &str, date: &str, record: &Record) {
+        let Some(path) = &self.log else { return; };
+        let _ = fs::create_dir_all(path.parent().unwrap_or(Path::new("/")));
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            if let Ok(line) = serde_json::to_string(&Line { date, what, record }) {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+
+    fn push_done(&mut self, date: String, record: Record) {
+        if JOURNAL_LEN == self.done.len() {
+            self.done.remove(0);
+        }
+        self.done.push((date, record));
+    }
+
+    pub fn record(&mut self, record: Record) {
+        let is_empty = match &record {
+            Record::Copy(v) | Record::Move(v) | Record::Rename(v) => v.is_empty(),
+            Record::Trash(v) | Record::Restore(v) => v.is_empty(),
+            Record::Delete(v) => v.is_empty(),
+        };
+        if is_empty {
+            return;
+        }
+        let date = time_to_string(SystemTime::now(), TimeFormat::Iso);
+        self.log("do", &date, &record);
+        self.push_done(date, record);
+        self.undone.clear();
+    }
+
+    /// reverse the last operation; `None` if nothing to undo,
+    /// else what was undone and the errors
+    pub fn undo(&mut self) -> Option<(Record, Vec<String>)> {
+        let (_, record) = self.done.pop()?;
+        let (redo, errors) = record.undo();
+        let date = time_to_string(SystemTime::now(), TimeFormat::Iso);
+        self.log("undo", &date, redo.as_ref().unwrap_or(&record));
+        if let Some(redo) = redo {
+            self.undone.push((date, redo));
+        }
+        Some((record, errors))
+    }
+
+    pub fn redo(&mut self) -> Option<(Record, Vec<String>)> {
+        let (_, record) = self.undone.pop()?;
+        let (done, errors) = record.redo();
+        let date = time_to_string(SystemTime::now(), TimeFormat::Iso);
+        self.log("redo", &date, done.as_ref().unwrap_or(&record));
+        if let Some(done) = done {
+            self.push_done(date, done);
+        }
+        Some((record, errors))
+    }
+
+    /// what happened, oldest first (undone operations are listed last)
+    pub fn list(&self) -> Vec<String> {
+        self.done
+            .iter()
+            .map(|(date, record)| format!("{date}  {}", record.describe()))
+            .chain(
+                self.undone
+                    .iter()
+                    .rev()
+                    .map(|(date, record)| format!("{date}  (undone) {}", record.describe())),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[derive(Deserialize)]
+    struct OwnedLine {
+        what: String,
+        record: Record,
+    }
+
+    fn read_log(path: &Path) -> Vec<(String, Record)> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let OwnedLine { what, record } = serde_json::from_str(line).unwrap();
+                (what, record)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_log() {
+        let dir = TestDir::new("journal-log");
+        let log = dir.join("journal.jsonl");
+        let pairs = vec![(dir.join("a"), dir.join("b\nc"))];
+        let mut journal = Journal::with_log(Some(log.clone()));
+        journal.record(Record::Copy(pairs.clone()));
+        journal.record(Record::Delete(Vec::new()));
+
+        let lines = read_log(&log);
+        assert_eq!(1, lines.len());
+        assert_eq!("do", lines[0].0);
+        assert!(matches!(&lines[0].1, Record::Copy(v) if *v == pairs));
+
+        let line = fs::read_to_string(&log).unwrap();
+        fs::write(&log, line.repeat(LOG_LEN + 5)).unwrap();
+        let journal = Journal::with_log(Some(log.clone()));
+        assert!(journal.list().is_empty());
+        assert_eq!(LOG_LEN, read_log(&log).len());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let dir = TestDir::new("journal-undo");
+        let (a, b) = (dir.join("a"), dir.join("b"));
+        fs::write(&a, "a").unwrap();
+        fs::rename(&a, &b).unwrap();
+        let mut journal = Journal::with_log(None);
+        journal.record(Record::Move(vec![(a.clone(), b.clone())]));
+
+        let (_, errors) = journal.undo().unwrap();
+        assert!(errors.is_empty());
+        assert!(a.exists() && !b.exists());
+        assert!(journal.undo().is_none());
+
+        let (_, errors) = journal.redo().unwrap();
+        assert!(errors.is_empty());
+        assert!(!a.exists() && b.exists());
+        assert!(journal.redo().is_none());
+
+        // the original came back meanwhile
+        fs::write(&a, "again").unwrap();
+        let (_, errors) = journal.undo().unwrap();
+        assert_eq!(1, errors.len());
+        assert_eq!("again", fs::read_to_string(&a).unwrap());
+        assert!(b.exists());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     commands::{Action, Key},
+    node::MetaFormat,
     textblock::TextBlock,
     tree::Tree,
     view::View,
@@ -25,24 +26,28 @@ fn str_char_slice(c: &str, a: usize, b: usize) -> &str {
     }
 }
 
-fn str_line_escape(c: &str) -> String {
+/// escape `c` so that it is read back as a single argument
+pub fn line_escape(c: &str) -> String {
     let mut r = String::new();
     r.reserve(c.len());
-    let mut last = 'x';
     for ch in c.chars() {
         match ch {
             '\'' | '"' | '\\' | '{' | ' ' | '\t' | '\n' | '\r' | '#' => r.push('\\'),
             _ => (),
         }
         r.push(ch);
-        last = ch;
     }
-    if ' ' == last {
+    r
+}
+
+fn str_line_escape(c: &str) -> String {
+    let mut r = line_escape(c);
+    if r.ends_with("\\ ") {
         r.pop();
         r.pop();
         r.push(' ');
     }
-    return r;
+    r
 }
 
 pub enum Message {
@@ -74,12 +79,25 @@ pub struct Status {
 pub struct Line<'me> {
     focused: &'me View,
     tree: &'me Tree,
+    format: &'me MetaFormat,
 }
 
 impl StatefulWidget for Line<'_> {
     type State = Status;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Status) {
+        // (also while prompting, eg. to list what is to be confirmed)
+        if let Some(m) = &state.message {
+            let (text, style) = match m {
+                Message::Info(text) => (text, Style::default()),
+                Message::Warning(text) => (text, Style::default().fg(Color::Yellow)),
+                Message::Error(text) => (text, Style::default().fg(Color::Red)),
+            };
+
+            let width = area.width as usize;
+            state.message_tb = Some(TextBlock::wrapped(text, width, style));
+        }
+
         if let Some(p) = &mut state.input {
             buf.set_spans(
                 area.x + 2,
@@ -153,23 +171,26 @@ impl StatefulWidget for Line<'_> {
             return;
         } // if Some state.input
 
-        if let Some(m) = &state.message {
-            let (text, style) = match m {
-                Message::Info(text) => (text, Style::default()),
-                Message::Warning(text) => (text, Style::default().fg(Color::Yellow)),
-                Message::Error(text) => (text, Style::default().fg(Color::Red)),
-            };
-
-            let width = area.width as usize;
-            state.message_tb = Some(TextBlock::wrapped(text, width, style));
-        } else {
+        if state.message.is_none() {
             let (_, node) = self.focused.at_cursor_pair(self.tree);
             buf.set_spans(
                 area.x + 1,
                 area.y,
                 &Spans::from(vec![
+                    Span::styled(
+                        if self.focused.in_visual() {
+                            "-- visual -- "
+                        } else {
+                            ""
+                        },
+                        Style::default().fg(Color::Yellow),
+                    ),
                     Span::raw(node.meta_to_string()),
                     Span::raw(" "),
+                    Span::raw(node.size_to_string(self.format)),
+                    Span::raw(" "),
+                    Span::raw(node.time_to_string(|m| m.modified, self.format)),
+                    Span::raw(" "),
                     Span::styled(node.file_name(), node.style()),
                     Span::raw(node.decoration()),
                 ]),
@@ -215,6 +236,7 @@ pub fn split_line_args_cursor_indices(
 
     let mut lookup_name = String::new();
     let mut lookup_cond = false;
+    let mut lookup_seen_cond = false;
 
     let mut last_k = 0;
     for (k, ch) in c.chars().enumerate() {
@@ -236,6 +258,7 @@ pub fn split_line_args_cursor_indices(
                         .first()
                         .map(|x| !x.is_empty())
                         .unwrap_or(false);
+                    lookup_seen_cond = true;
                     lookup_name.clear();
                 }
                 (':', true) | ('}', false) => {
@@ -257,12 +280,17 @@ pub fn split_line_args_cursor_indices(
                     }
                     lookup_name.clear();
                 }
+                // `{selection:name}` is not an 'else' branch
+                (':', false) if !lookup_seen_cond && "selection" == lookup_name => {
+                    lookup_name.push(ch)
+                }
                 (':', false) => lookup_name.clear(),
                 _ => lookup_name.push(ch),
             }
             if '}' == ch {
                 in_lookup = false;
                 lookup_cond = false;
+                lookup_seen_cond = false;
             }
         } else if in_simple {
             if '\'' == ch {
@@ -389,6 +417,13 @@ impl Status {
         });
     }
 
+    /// move the cursor of the current prompt, if any
+    pub fn prompt_cursor(&mut self, at: usize) {
+        if let Some(p) = &mut self.input {
+            p.cursor = at.min(p.content.chars().count());
+        }
+    }
+
     pub fn do_event(
         &mut self,
         event: &Event,
@@ -608,8 +643,16 @@ impl Status {
 }
 
 impl Line<'_> {
-    pub fn new<'app>(focused: &'app View, tree: &'app Tree) -> Line<'app> {
-        Line { focused, tree }
+    pub fn new<'app>(
+        focused: &'app View,
+        tree: &'app Tree,
+        format: &'app MetaFormat,
+    ) -> Line<'app> {
+        Line {
+            focused,
+            tree,
+            format,
+        }
     }
 }
 
@@ -763,7 +806,18 @@ fn complete(p: &mut Prompt, lookup: &impl Fn(&str) -> Vec<String>) {
 
     let res = if 0 != in_obj {
         if 0 != in_ppt {
-            ["relative", "file-name", "extension"]
+            [
+                "relative",
+                "file-name",
+                "extension",
+                "size",
+                "atime",
+                "mtime",
+                "ctime",
+                "mime",
+                "type",
+                "pointer",
+            ]
                 .iter()
                 .filter(|it| it.starts_with(&ppt))
                 .map(|it| " ".repeat(in_ppt) + it)
@@ -883,6 +937,8 @@ mod tests {
                 ["sh", "-c", "xxd -g1 'name:some' | hx"]
             ),
             (r#"message info a "" c"#, ["message", "info", "a", "", "c"]),
+            (r#"a {selection:b} c"#, ["a", "name:selection:b", "c"]),
+            (r#"a {w?x:y} c"#, ["a", "name:x", "c"]),
         ];
     }
 }
@@ -0,0 +1,219 @@
+// file type detection from the content (magic bytes), falling back
+// to the extension
+use crate::source::TreeSource;
+use std::{io, path::Path};
+use tui::style::{Color, Modifier, Style};
+
+/// how much is read from the start of a file to detect its type
+const SNIFF_BYTES: u64 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Magic {
+    pub mime: &'static str,
+    /// a short name, eg. `elf`, `png`, `script`, `text`...
+    pub kind: &'static str,
+}
+
+const fn magic(mime: &'static str, kind: &'static str) -> Magic {
+    Magic { mime, kind }
+}
+
+pub const DIRECTORY: Magic = magic("inode/directory", "directory");
+pub const SYMLINK: Magic = magic("inode/symlink", "link");
+pub const FIFO: Magic = magic("inode/fifo", "fifo");
+pub const SOCKET: Magic = magic("inode/socket", "socket");
+pub const DEVICE: Magic = magic("inode/device", "device");
+pub const EMPTY: Magic = magic("inode/x-empty", "empty");
+pub const TEXT: Magic = magic("text/plain", "text");
+pub const BINARY: Magic = magic("application/octet-stream", "binary");
+
+/// (offset, bytes) to look for, and what it means
+const SIGNATURES: &[(usize, &[u8], Magic)] = &[
+    (0, b"\x7fELF", magic("application/x-executable", "elf")),
+    (
+        0,
+        b"\xfe\xed\xfa\xce",
+        magic("application/x-mach-binary", "mach-o"),
+    ),
+    (
+        0,
+        b"\xfe\xed\xfa\xcf",
+        magic("application/x-mach-binary", "mach-o"),
+    ),
+    (
+        0,
+        b"\xcf\xfa\xed\xfe",
+        magic("application/x-mach-binary", "mach-o"),
+    ),
+    (
+        0,
+        b"MZ",
+        magic("application/vnd.microsoft.portable-executable", "pe"),
+    ),
+    (0, b"\0asm", magic("application/wasm", "wasm")),
+    (0, b"\x89PNG\r\n\x1a\n", magic("image/png", "png")),
+    (0, b"\xff\xd8\xff", magic("image/jpeg", "jpeg")),
+    (0, b"GIF87a", magic("image/gif", "gif")),
+    (0, b"GIF89a", magic("image/gif", "gif")),
+    (0, b"BM", magic("image/bmp", "bmp")),
+    (8, b"WEBP", magic("image/webp", "webp")),
+    (0, b"%PDF-", magic("application/pdf", "pdf")),
+    (0, b"\x1f\x8b", magic("application/gzip", "gzip")),
+    (0, b"PK\x03\x04", magic("application/zip", "zip")),
+    (0, b"PK\x05\x06", magic("application/zip", "zip")),
+    (0, b"\x28\xb5\x2f\xfd", magic("application/zstd", "zstd")),
+    (0, b"BZh", magic("application/x-bzip2", "bzip2")),
+    (0, b"\xfd7zXZ\0", magic("application/x-xz", "xz")),
+    (
+        0,
+        b"7z\xbc\xaf\x27\x1c",
+        magic("application/x-7z-compressed", "7z"),
+    ),
+    (257, b"ustar", magic("application/x-tar", "tar")),
+    (
+        0,
+        b"SQLite format 3\0",
+        magic("application/vnd.sqlite3", "sqlite"),
+    ),
+    (0, b"ID3", magic("audio/mpeg", "mp3")),
+    (0, b"OggS", magic("audio/ogg", "ogg")),
+    (0, b"fLaC", magic("audio/flac", "flac")),
+    (8, b"WAVE", magic("audio/wav", "wav")),
+    (4, b"ftyp", magic("video/mp4", "mp4")),
+    (0, b"\x1a\x45\xdf\xa3", magic("video/x-matroska", "mkv")),
+];
+
+/// NUL bytes or invalid UTF-8 (not counting a character cut at
+/// the end of the block)
+pub fn is_binary(block: &[u8]) -> bool {
+    block.contains(&0) || std::str::from_utf8(block).is_err_and(|err| err.error_len().is_some())
+}
+
+/// guess from the extension alone
+pub fn mime_by_extension(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    Some(match ext.as_str() {
+        "txt" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "rs" => "text/rust",
+        "c" | "h" => "text/x-c",
+        "py" => "text/x-python",
+        "sh" => "application/x-sh",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+fn script(first_line: &[u8]) -> Magic {
+    let line = String::from_utf8_lossy(first_line);
+    let mut words = line.split_whitespace();
+    let mut interp = words
+        .next()
+        .and_then(|it| it.rsplit('/').next())
+        .unwrap_or("");
+    if "env" == interp {
+        interp = words.find(|it| !it.starts_with('-')).unwrap_or("");
+    }
+    match interp.trim_end_matches(|c: char| c.is_ascii_digit() || '.' == c) {
+        "sh" | "bash" | "dash" | "zsh" | "ksh" | "ash" => magic("application/x-sh", "script"),
+        "python" => magic("text/x-python", "script"),
+        "perl" => magic("text/x-perl", "script"),
+        "ruby" => magic("text/x-ruby", "script"),
+        "node" => magic("text/javascript", "script"),
+        _ => magic("text/x-script", "script"),
+    }
+}
+
+/// type of the content of a file from its first bytes
+pub fn detect_bytes(path: &Path, block: &[u8]) -> Magic {
+    if block.is_empty() {
+        return EMPTY;
+    }
+    if let Some(rest) = block.strip_prefix(b"#!") {
+        return script(rest.split(|b| b'\n' == *b).next().unwrap_or(rest));
+    }
+    let found = SIGNATURES
+        .iter()
+        // (too short to be trusted on text)
+        .find(|(at, sig, _)| {
+            block.get(*at..at + sig.len()) == Some(sig) && (2 < sig.len() || is_binary(block))
+        });
+    if let Some((_, _, it)) = found {
+        return *it;
+    }
+    if is_binary(block) {
+        return BINARY;
+    }
+    match mime_by_extension(path) {
+        Some(mime) => magic(mime, "text"),
+        None => TEXT,
+    }
+}
+
+/// when the content is not at hand (eg. an entry in an archive)
+pub fn detect_name(path: &Path) -> Magic {
+    match mime_by_extension(path) {
+        Some(mime) => magic(mime, "text"),
+        None => BINARY,
+    }
+}
+
+/// type of the content of a regular file (anything else is an error)
+pub fn detect(source: &dyn TreeSource, path: &Path) -> io::Result<Magic> {
+    let block = source.read_at(path, 0, SNIFF_BYTES)?;
+    Ok(detect_bytes(path, &block))
+}
+
+impl Magic {
+    /// when nothing better is known (eg. from `LS_COLORS`)
+    pub fn style(&self) -> Option<Style> {
+        let (top, _) = self.mime.split_once('/')?;
+        let bold = |color| Some(Style::default().fg(color).add_modifier(Modifier::BOLD));
+        match (top, self.kind) {
+            (_, "elf" | "mach-o" | "pe" | "wasm" | "script") => bold(Color::Green),
+            (_, "gzip" | "zip" | "zstd" | "bzip2" | "xz" | "7z" | "tar") => bold(Color::Red),
+            ("image" | "video", _) => bold(Color::Magenta),
+            ("audio", _) => Some(Style::default().fg(Color::Cyan)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_bytes() {
+        let kind = |name: &str, bytes: &[u8]| detect_bytes(Path::new(name), bytes).kind;
+        assert_eq!(kind("a", b"\x7fELF\x02\x01"), "elf");
+        assert_eq!(kind("a.txt", b"\x89PNG\r\n\x1a\n...."), "png");
+        assert_eq!(kind("a", b"#!/usr/bin/env python3\nprint()"), "script");
+        assert_eq!(
+            detect_bytes(Path::new("a"), b"#!/bin/sh\n").mime,
+            "application/x-sh"
+        );
+        assert_eq!(kind("a", b"hello"), "text");
+        assert_eq!(
+            detect_bytes(Path::new("a.json"), b"{}").mime,
+            "application/json"
+        );
+        assert_eq!(kind("a", b"\x01\x00\x02"), "binary");
+        assert_eq!(kind("a", b"ab\xffc"), "binary");
+        // a character cut at the end of the block
+        assert_eq!(kind("a", "aé".as_bytes().split_last().unwrap().1), "text");
+        assert_eq!(kind("a", b"MZ is not always an executable"), "text");
+        assert_eq!(kind("a", b""), "empty");
+        let mut tar = vec![0; 300];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(kind("a", &tar), "tar");
+    }
+}
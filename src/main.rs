@@ -1,20 +1,49 @@
 mod all_the_stuff;
 mod app;
+mod archive;
 mod args;
 mod commands;
 mod completions;
+mod document;
+mod fileop;
+mod fuzzy;
+mod highlight;
+mod info;
+mod journal;
 mod line;
+mod magic;
 mod node;
+mod pathlist;
+mod perms;
+mod preview;
+mod source;
+#[cfg(test)]
+mod testdir;
 mod textblock;
+mod trash;
 mod tree;
 mod view;
 
 use crate::{all_the_stuff::AllTheStuff, args::Args};
 use clap::Parser;
-use std::error::Error;
+use std::{
+    error::Error,
+    io::{self, Write},
+};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let crap = AllTheStuff::new(Args::parse())?;
+    let args = Args::parse();
+    let (print, ascii) = (args.print, args.ascii);
+    let app = AllTheStuff::load_app(args)?;
+
+    // like `tree`, without ever entering the alternate screen
+    if print {
+        let (view, tree) = app.focused_and_tree();
+        io::stdout().lock().write_all(tree.print(view, ascii).as_bytes())?;
+        return Ok(());
+    }
+
+    let crap = AllTheStuff::new(app)?;
     crap.run()?;
 
     // // TODO: get each views' root paths and save individually there
@@ -1,13 +1,22 @@
+use crate::{
+    archive::{self, Header},
+    document::{self, Kind, Member},
+    magic::{self, Magic},
+    source::{self, EntryKind, Meta, TreeSource},
+};
 use glob::Pattern;
-use lscolors::LsColors;
+use lscolors::{Indicator, LsColors};
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::OnceCell,
     cmp::Ordering,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt,
-    fs::{metadata, read_link, read_to_string, symlink_metadata, Metadata},
+    fs::{read_to_string, Metadata},
     io,
     path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tui::style::{Color, Modifier, Style};
 
@@ -17,7 +26,7 @@ pub enum Movement {
     Backward = -1,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum SortingProp {
     None,
     Name,
@@ -28,7 +37,7 @@ pub enum SortingProp {
     CTime,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Sorting {
     pub prop: SortingProp,
     pub dirs_first: bool,
@@ -40,10 +49,34 @@ impl Sorting {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum SizeFormat {
+    Bytes,
+    #[default]
+    Iec,
+    Si,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum TimeFormat {
+    Raw,
+    #[default]
+    Relative,
+    Iso,
+}
+
+/// how sizes and times are displayed (status line, interpolation...)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct MetaFormat {
+    pub size: SizeFormat,
+    pub time: TimeFormat,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Filtering {
     Pattern(String),                 // XXX: cannot serialize Pattern
     IgnoreFile(String, Vec<String>), // same as above (the Vec)
+    Kind(String),                    // pattern on the type or MIME type
 }
 
 impl PartialEq for Filtering {
@@ -51,6 +84,7 @@ impl PartialEq for Filtering {
         match (self, other) {
             (Filtering::Pattern(a), Filtering::Pattern(b)) => a == b,
             (Filtering::IgnoreFile(a, _), Filtering::IgnoreFile(b, _)) => a == b,
+            (Filtering::Kind(a), Filtering::Kind(b)) => a == b,
             _ => false,
         }
     }
@@ -63,6 +97,7 @@ impl fmt::Display for Filtering {
             Filtering::IgnoreFile(name, pats) => {
                 write!(f, "# {name} ({} patterns)\n{}", pats.len(), pats.join("\n"))
             }
+            Filtering::Kind(pat) => write!(f, "kind:{pat}"),
         }
     }
 }
@@ -104,11 +139,15 @@ impl Filtering {
             Filtering::IgnoreFile(_, pats) => {
                 pats.iter().any(|pat| Filtering::_matches_one(pat, node))
             }
+            Filtering::Kind(pat) => Pattern::new(pat).is_ok_and(|p| {
+                let magic = node.magic();
+                p.matches(magic.kind) || p.matches(magic.mime)
+            }),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum FileKind {
     NamedPipe,
     CharDevice,
@@ -118,19 +157,60 @@ pub enum FileKind {
     Executable,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum NodeInfo {
     Dir { loaded: bool, children: Vec<Node> },
     Link { target: Result<Box<Node>, PathBuf> },
     File { kind: FileKind },
+    /// an archive file (no header) or an entry in it; entries
+    /// only exist in the tree, they have no metadata
+    Archive {
+        header: Option<Header>,
+        loaded: bool,
+        children: Vec<Node>,
+    },
+    /// a document file (no member) or a value in it; same as for
+    /// archives, values only exist in the tree
+    Document {
+        member: Option<Member>,
+        loaded: bool,
+        children: Vec<Node>,
+    },
+    /// a folder with only the paths that were listed (eg. from
+    /// stdin), it may or may not exist
+    Listed { children: Vec<Node> },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// what `Node::renew_within` changed in the tree
+pub enum Renewed {
+    /// a node read again, this is the one it replaced
+    Node(Node),
+    /// a directory listed again, with the names of its children
+    /// before (in the same order)
+    Listing(PathBuf, Vec<String>),
+}
+
+impl Renewed {
+    pub fn path(&self) -> &Path {
+        match self {
+            Renewed::Node(node) => node.as_path(),
+            Renewed::Listing(path, _) => path,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Node {
     path: PathBuf,
     #[serde(skip_serializing, skip_deserializing)]
-    meta: Option<Metadata>,
+    meta: Option<Meta>,
     info: NodeInfo,
+    /// detected from the content, only when first needed
+    #[serde(skip_serializing, skip_deserializing)]
+    magic: OnceCell<Magic>,
+    /// where it (and its children) comes from
+    #[serde(skip_serializing, skip_deserializing, default = "source::local")]
+    source: Rc<dyn TreeSource>,
 }
 
 impl fmt::Display for Node {
@@ -157,6 +237,13 @@ impl fmt::Display for Node {
                 }
             }
 
+            NodeInfo::Listed { children } => {
+                writeln!(f, "{indent}{name}/")?;
+                children
+                    .iter()
+                    .try_for_each(|ch| write!(f, "{ch:.*}", depth + 1))
+            }
+
             NodeInfo::Link { target } => {
                 write!(f, "{indent}{name}@ -> ")?;
                 match target {
@@ -171,35 +258,42 @@ impl fmt::Display for Node {
                 FileKind::Executable => writeln!(f, "{indent}{name}*"),
                 _ => writeln!(f, "{indent}{name}"),
             },
+
+            NodeInfo::Archive { header, children, .. } => {
+                let slash = if header.is_some_and(|h| h.dir) { "/" } else { "" };
+                writeln!(f, "{indent}{name}{slash}")?;
+                children
+                    .iter()
+                    .try_for_each(|ch| write!(f, "{ch:.*}", depth + 1))
+            }
+
+            NodeInfo::Document { children, .. } => {
+                writeln!(f, "{indent}{name}{}", self.decoration())?;
+                children
+                    .iter()
+                    .try_for_each(|ch| write!(f, "{ch:.*}", depth + 1))
+            }
         }
     }
 }
 
 #[cfg(unix)]
 impl FileKind {
-    fn from(_path: &Path, meta: &Metadata) -> FileKind {
-        use std::os::unix::fs::FileTypeExt;
-        use std::os::unix::fs::PermissionsExt;
-        let ft = meta.file_type();
-        if ft.is_fifo() {
-            FileKind::NamedPipe
-        } else if ft.is_char_device() {
-            FileKind::CharDevice
-        } else if ft.is_block_device() {
-            FileKind::BlockDevice
-        } else if ft.is_socket() {
-            FileKind::Socket
-        } else if meta.permissions().mode() & 0o111 != 0 {
-            FileKind::Executable
-        } else {
-            FileKind::Regular
+    fn from(_path: &Path, meta: &Meta) -> FileKind {
+        match meta.kind {
+            EntryKind::Fifo => FileKind::NamedPipe,
+            EntryKind::CharDevice => FileKind::CharDevice,
+            EntryKind::BlockDevice => FileKind::BlockDevice,
+            EntryKind::Socket => FileKind::Socket,
+            _ if meta.mode & 0o111 != 0 => FileKind::Executable,
+            _ => FileKind::Regular,
         }
     }
 }
 
 #[cfg(windows)]
 impl FileKind {
-    fn from(path: &Path, _meta: &Metadata) -> FileKind {
+    fn from(path: &Path, _meta: &Meta) -> FileKind {
         if path.ends_with(".exe") || path.ends_with(".com") {
             FileKind::Executable
         } else {
@@ -218,68 +312,203 @@ fn perm_to_string(o: u32) -> String {
     .collect()
 }
 
-#[cfg(unix)]
-fn meta_to_string(meta: &Metadata) -> String {
-    use std::os::unix::fs::FileTypeExt;
-    use std::os::unix::fs::PermissionsExt;
-
-    let mode = meta.permissions().mode();
-    let ft = meta.file_type();
-
+/// eg. `rwxr-xr-x` (only the permission bits)
+pub fn mode_to_string(mode: u32) -> String {
     [
-        // file type
-        if ft.is_block_device() {
-            'b'
-        } else if ft.is_char_device() {
-            'c'
-        } else if ft.is_dir() {
-            'd'
-        } else if ft.is_symlink() {
-            'l'
-        } else if ft.is_fifo() {
-            'p'
-        } else if ft.is_socket() {
-            's'
-        } else {
-            '-'
-        }
-        .to_string(),
-        // owner
         perm_to_string(mode >> 6 & 0b111),
-        // group
         perm_to_string(mode >> 3 & 0b111),
-        // world
         perm_to_string(mode & 0b111),
     ]
     .concat()
 }
 
-#[cfg(windows)]
-fn meta_to_string(meta: &Metadata) -> String {
-    //use std::os::windows::fs::FileTypeExt;
-    //use std::os::windows::fs::PermissionsExt;
+fn meta_to_string(meta: &Meta) -> String {
+    let kind = match meta.kind {
+        EntryKind::BlockDevice => 'b',
+        EntryKind::CharDevice => 'c',
+        EntryKind::Dir => 'd',
+        EntryKind::Symlink => 'l',
+        EntryKind::Fifo => 'p',
+        EntryKind::Socket => 's',
+        EntryKind::File => '-',
+    };
+    // owner, group, world
+    format!("{kind}{}", mode_to_string(meta.mode))
+}
 
-    let ro = meta.permissions().readonly();
-    let ft = meta.file_type();
+pub fn size_to_string(len: u64, format: SizeFormat) -> String {
+    let (base, units): (f64, &[&str]) = match format {
+        SizeFormat::Bytes => return len.to_string(),
+        SizeFormat::Iec => (1024.0, &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+        SizeFormat::Si => (1000.0, &["B", "kB", "MB", "GB", "TB", "PB", "EB"]),
+    };
+    let mut n = len as f64;
+    let mut k = 0;
+    while base <= n && k + 1 < units.len() {
+        n /= base;
+        k += 1;
+    }
+    if 0 == k {
+        format!("{len}{}", units[0])
+    } else if n < 10.0 {
+        format!("{n:.1}{}", units[k])
+    } else {
+        format!("{n:.0}{}", units[k])
+    }
+}
 
-    [
-        // file type
-        if ft.is_dir() {
-            'd'
-        } else if ft.is_symlink() {
-            'l'
-        } else {
-            '-'
-        }
-        .to_string(),
-        // owner
-        perm_to_string(0b101 | if ro { 0b000 } else { 0b010 }),
-        // group
-        perm_to_string(0b101 | if ro { 0b000 } else { 0b010 }),
-        // world
-        perm_to_string(0b101 | if ro { 0b000 } else { 0b010 }),
-    ]
-    .concat()
+// days since epoch to (year, month, day), see
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = if 0 <= z { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + if m <= 2 { 1 } else { 0 }, m, d)
+}
+
+fn secs_to_iso(secs: i64) -> String {
+    let (y, m, d) = civil_from_days(secs.div_euclid(86400));
+    let t = secs.rem_euclid(86400);
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+        t / 3600,
+        t / 60 % 60,
+        t % 60
+    )
+}
+
+fn secs_to_relative(delta: i64) -> String {
+    let (n, unit) = match delta.abs() {
+        a if a < 60 => (a, "s"),
+        a if a < 3600 => (a / 60, "m"),
+        a if a < 86400 => (a / 3600, "h"),
+        a if a < 30 * 86400 => (a / 86400, "d"),
+        a if a < 365 * 86400 => (a / (30 * 86400), "mo"),
+        a => (a / (365 * 86400), "y"),
+    };
+    if delta < 0 {
+        format!("in {n}{unit}")
+    } else {
+        format!("{n}{unit} ago")
+    }
+}
+
+pub fn time_to_string(time: SystemTime, format: TimeFormat) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    match format {
+        TimeFormat::Raw => secs.to_string(),
+        TimeFormat::Relative => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64);
+            secs_to_relative(now - secs)
+        }
+        TimeFormat::Iso => secs_to_iso(secs),
+    }
+}
+
+/// the entries of the archive at `path` as a tree, with the
+/// directories that are not listed made up
+fn archive_children(
+    source: &Rc<dyn TreeSource>,
+    path: &Path,
+    entries: Vec<archive::Entry>,
+) -> Vec<Node> {
+    let mut under = HashMap::<_, Vec<(PathBuf, Header)>>::new();
+    let mut known = HashSet::new();
+    for ent in entries {
+        let (mut rel, mut header) = (ent.path, ent.header);
+        let mut listed = true;
+        loop {
+            let parent = rel.parent().unwrap_or(Path::new("")).to_path_buf();
+            if known.contains(&rel) {
+                // listed after what it contains, or twice
+                if listed {
+                    let siblings = under.get_mut(&parent).unwrap();
+                    siblings.iter_mut().find(|(it, _)| *it == rel).unwrap().1 = header;
+                }
+                break;
+            }
+            known.insert(rel.clone());
+            under.entry(parent.clone()).or_default().push((rel, header));
+            if parent.as_os_str().is_empty() {
+                break;
+            }
+            (rel, header, listed) = (parent, Header { dir: true, ..Header::default() }, false);
+        }
+    }
+
+    type Under = HashMap<PathBuf, Vec<(PathBuf, Header)>>;
+    fn build(source: &Rc<dyn TreeSource>, path: &Path, rel: &Path, under: &mut Under) -> Vec<Node> {
+        under
+            .remove(rel)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(rel, header)| Node {
+                path: path.join(&rel),
+                meta: None,
+                info: NodeInfo::Archive {
+                    header: Some(header),
+                    loaded: true,
+                    children: if header.dir {
+                        build(source, path, &rel, under)
+                    } else {
+                        Vec::new()
+                    },
+                },
+                magic: OnceCell::new(),
+                source: source.clone(),
+            })
+            .collect()
+    }
+    build(source, path, Path::new(""), &mut under)
+}
+
+/// the values in `value` (at `pointer` in the document at `path`)
+fn document_children(
+    source: &Rc<dyn TreeSource>,
+    path: &Path,
+    pointer: &str,
+    value: &serde_json::Value,
+) -> Vec<Node> {
+    let members = match value {
+        serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        serde_json::Value::Array(v) => v
+            .iter()
+            .enumerate()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+        _ => Vec::new(),
+    };
+    members
+        .into_iter()
+        .map(|(key, value)| {
+            let path = path.join(document::name(&key));
+            let pointer = format!("{pointer}/{}", document::escape(&key));
+            Node {
+                info: NodeInfo::Document {
+                    children: document_children(source, &path, &pointer, value),
+                    member: Some(Member {
+                        pointer,
+                        kind: Kind::of(value),
+                    }),
+                    loaded: true,
+                },
+                path,
+                meta: None,
+                magic: OnceCell::new(),
+                source: source.clone(),
+            }
+        })
+        .collect()
 }
 
 fn cmp_in<T, C: Ord>(l: &Option<T>, r: &Option<T>, sel: fn(&T) -> C) -> Ordering {
@@ -350,7 +579,13 @@ fn lscolors_to_tui_style(lsy: &lscolors::style::Style) -> Style {
 }
 
 impl Node {
+    /// from the local file system
     pub fn new(path: PathBuf, meta: Metadata) -> io::Result<Node> {
+        Node::new_in(&source::local(), path, meta.into())
+    }
+
+    /// `meta` is from `source.symlink_metadata`
+    pub fn new_in(source: &Rc<dyn TreeSource>, path: PathBuf, meta: Meta) -> io::Result<Node> {
         let info = if meta.is_dir() {
             NodeInfo::Dir {
                 loaded: false,
@@ -359,13 +594,26 @@ impl Node {
         } else if meta.is_symlink() {
             NodeInfo::Link {
                 target: {
-                    let realpath = read_link(path.clone())?;
-                    symlink_metadata(realpath.clone())
-                        .and_then(|lnmeta| Node::new(realpath.clone(), lnmeta))
+                    let realpath = source.read_link(&path)?;
+                    source
+                        .symlink_metadata(&realpath)
+                        .and_then(|lnmeta| Node::new_in(source, realpath.clone(), lnmeta))
                         .map(Box::new)
                         .map_err(|_| realpath)
                 },
             }
+        } else if meta.is_file() && document::is_document(&path) {
+            NodeInfo::Document {
+                member: None,
+                loaded: false,
+                children: Vec::new(),
+            }
+        } else if meta.is_file() && archive::is_archive(&path) {
+            NodeInfo::Archive {
+                header: None,
+                loaded: false,
+                children: Vec::new(),
+            }
         } else {
             NodeInfo::File {
                 kind: FileKind::from(&path, &meta),
@@ -376,11 +624,45 @@ impl Node {
             path,
             meta: Some(meta),
             info,
+            magic: OnceCell::new(),
+            source: source.clone(),
         })
     }
 
+    /// a node for `path` from the same source as this one, whether
+    /// it is loaded in the tree or not
+    pub fn new_sibling(&self, path: PathBuf) -> io::Result<Node> {
+        let meta = self.source.symlink_metadata(&path)?;
+        Node::new_in(&self.source, path, meta)
+    }
+
+    /// the entries of the directory at `path`, as nodes
+    fn read_dir(source: &Rc<dyn TreeSource>, path: &Path) -> io::Result<Vec<Node>> {
+        Ok(source
+            .read_dir(path)?
+            .into_iter()
+            .filter_map(|(path, meta)| Node::new_in(source, path, meta).ok())
+            .collect())
+    }
+
     pub fn renew(&self) -> io::Result<Node> {
-        let mut node = Node::new(self.path.clone(), metadata(&self.path)?)?;
+        // the list is what matters, not what is on disk
+        if let NodeInfo::Listed { children } = &self.info {
+            return Ok(Node::listed(
+                &self.source,
+                self.path.clone(),
+                children
+                    .iter()
+                    .map(|ch| {
+                        ch.renew()
+                            .unwrap_or_else(|_| Node::missing(&self.source, ch.path.clone()))
+                    })
+                    .collect(),
+            ));
+        }
+
+        let meta = self.source.metadata(&self.path)?;
+        let mut node = Node::new_in(&self.source, self.path.clone(), meta)?;
 
         // correct for any change, load children if any
         // (does not account for eg. dir became a link)
@@ -405,12 +687,13 @@ impl Node {
                 }
 
                 children.extend(
-                    self.path
-                        .read_dir()?
-                        .filter_map(Result::ok)
-                        .filter(|ent| !done.contains(ent.file_name().to_str().unwrap()))
-                        .map(|ent| ent.metadata().and_then(|meta| Node::new(ent.path(), meta)))
-                        .filter_map(Result::ok),
+                    self.source
+                        .read_dir(&self.path)?
+                        .into_iter()
+                        .filter(|(path, _)| {
+                            !done.contains(path.file_name().unwrap().to_str().unwrap())
+                        })
+                        .filter_map(|(path, meta)| Node::new_in(&self.source, path, meta).ok()),
                 );
 
                 *loaded = true;
@@ -427,46 +710,286 @@ impl Node {
             _ => (),
         }
 
+        // a file that did not change need not be read again (archives
+        // and documents are decompressed/parsed whole, and this is done
+        // on every update)
+        let unchanged = match (&self.meta, &node.meta) {
+            (Some(pm), Some(nm)) => {
+                pm.is_file() && nm.is_file() && (pm.len, pm.modified) == (nm.len, nm.modified)
+            }
+            _ => false,
+        };
+
+        // it was an opened archive, read it again if it changed
+        // (if it cannot be anymore, it is left unloaded)
+        if let (NodeInfo::Archive { loaded: true, .. }, NodeInfo::Archive { .. }) =
+            (&self.info, &node.info)
+        {
+            if unchanged {
+                node.info = self.info.clone();
+            } else {
+                let _ = node.load_children();
+            }
+        }
+
+        // it was a document (maybe only because it is the root),
+        // keep it one and read it again if it changed
+        if let (
+            NodeInfo::Document { member: None, loaded, .. },
+            NodeInfo::File { .. } | NodeInfo::Document { .. },
+        ) = (&self.info, &node.info)
+        {
+            node.info = NodeInfo::Document {
+                member: None,
+                loaded: false,
+                children: Vec::new(),
+            };
+            if *loaded && unchanged {
+                node.info = self.info.clone();
+            } else if *loaded {
+                let _ = node.load_children();
+            }
+        }
+
+        // no need to sniff the content again either
+        if let (Some(magic), true) = (self.magic.get(), unchanged) {
+            let _ = node.magic.set(*magic);
+        }
+
         Ok(node)
     }
 
+    /// like `renew` but only for the given `paths` (eg. after a file
+    /// operation) and in place, the rest of the tree is kept as is;
+    /// @ret what changed, for the views to follow (nothing changed on
+    /// error)
+    pub fn renew_within(&mut self, paths: &[PathBuf]) -> io::Result<Vec<Renewed>> {
+        let mut r = Vec::new();
+        self.renew_within_into(paths, &mut r)?;
+        Ok(r)
+    }
+
+    fn renew_within_into(&mut self, paths: &[PathBuf], out: &mut Vec<Renewed>) -> io::Result<()> {
+        if paths.contains(&self.path) {
+            let niw = self.renew()?;
+            out.push(Renewed::Node(std::mem::replace(self, niw)));
+            return Ok(());
+        }
+        if !paths.iter().any(|it| it.starts_with(&self.path)) {
+            return Ok(());
+        }
+
+        let Node { path, source, info, .. } = self;
+        match info {
+            NodeInfo::Listed { children } => {
+                for ch in children {
+                    if ch.renew_within_into(paths, out).is_err() {
+                        let missing = Node::missing(source, ch.path.clone());
+                        out.push(Renewed::Node(std::mem::replace(ch, missing)));
+                    }
+                }
+            }
+
+            NodeInfo::Dir { loaded: true, children } => {
+                let names = children.iter().map(|ch| ch.file_name().to_string()).collect();
+
+                if paths.iter().any(|it| it.parent() == Some(path)) {
+                    // entries were added/removed here, list it again
+                    let listing = source.read_dir(path)?;
+                    let mut previous = std::mem::take(children);
+                    for (path, meta) in listing {
+                        let name = path.file_name();
+                        let ch = match previous.iter().position(|ch| ch.path.file_name() == name) {
+                            Some(k) => {
+                                let mut ch = previous.swap_remove(k);
+                                ch.renew_within_into(paths, out).map(|()| ch)
+                            }
+                            None => Node::new_in(source, path, meta),
+                        };
+                        if let Ok(ch) = ch {
+                            children.push(ch);
+                        }
+                    }
+                    out.push(Renewed::Listing(path.clone(), names));
+                } else {
+                    let count = children.len();
+                    children.retain_mut(|ch| ch.renew_within_into(paths, out).is_ok());
+                    if children.len() != count {
+                        out.push(Renewed::Listing(path.clone(), names));
+                    }
+                }
+            }
+
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// anything else than a directory is read as a document
     pub fn new_root(path: PathBuf) -> io::Result<Node> {
-        let meta = Some(metadata(&path)?);
-        Ok(Node {
-            path,
-            meta,
-            info: NodeInfo::Dir {
+        Node::new_root_in(&source::local(), path)
+    }
+
+    pub fn new_root_in(source: &Rc<dyn TreeSource>, path: PathBuf) -> io::Result<Node> {
+        let meta = source.metadata(&path)?;
+        let info = if meta.is_dir() {
+            NodeInfo::Dir {
                 loaded: false,
                 children: Vec::new(),
-            },
+            }
+        } else {
+            NodeInfo::Document {
+                member: None,
+                loaded: false,
+                children: Vec::new(),
+            }
+        };
+        Ok(Node {
+            path,
+            meta: Some(meta),
+            info,
+            magic: OnceCell::new(),
+            source: source.clone(),
         })
     }
 
+    /// a tree of only the given paths (which are expected to be
+    /// under `root`), with the folders between them made up
+    pub fn from_paths(root: PathBuf, paths: Vec<PathBuf>) -> Node {
+        let mut under = HashMap::<_, Vec<PathBuf>>::new();
+        let mut known = HashSet::new();
+        for mut path in paths {
+            while path.starts_with(&root) && path != root && known.insert(path.clone()) {
+                let parent = path.parent().unwrap().to_path_buf();
+                under.entry(parent.clone()).or_default().push(path);
+                path = parent;
+            }
+        }
+
+        type Under = HashMap<PathBuf, Vec<PathBuf>>;
+        fn build(source: &Rc<dyn TreeSource>, path: PathBuf, under: &mut Under) -> Node {
+            match under.remove(&path) {
+                Some(listed) => {
+                    let children = listed
+                        .into_iter()
+                        .map(|it| build(source, it, under))
+                        .collect();
+                    Node::listed(source, path, children)
+                }
+                None => source
+                    .symlink_metadata(&path)
+                    .and_then(|meta| Node::new_in(source, path.clone(), meta))
+                    .unwrap_or_else(|_| Node::missing(source, path)),
+            }
+        }
+        // (the root is a listing even if nothing under it was listed)
+        let source = source::local();
+        let children = under
+            .remove(&root)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|it| build(&source, it, &mut under))
+            .collect();
+        Node::listed(&source, root, children)
+    }
+
+    fn listed(source: &Rc<dyn TreeSource>, path: PathBuf, children: Vec<Node>) -> Node {
+        Node {
+            meta: source.metadata(&path).ok(),
+            path,
+            info: NodeInfo::Listed { children },
+            magic: OnceCell::new(),
+            source: source.clone(),
+        }
+    }
+
+    /// a listed path that does not exist (anymore)
+    fn missing(source: &Rc<dyn TreeSource>, path: PathBuf) -> Node {
+        Node {
+            path,
+            meta: None,
+            info: NodeInfo::File {
+                kind: FileKind::Regular,
+            },
+            magic: OnceCell::new(),
+            source: source.clone(),
+        }
+    }
+
     pub fn as_path(&self) -> &Path {
         self.path.as_path()
     }
 
+    pub fn source(&self) -> &Rc<dyn TreeSource> {
+        &self.source
+    }
+
+    /// the path, or `<document>#<JSON pointer>` for a value in a
+    /// document (which is not a path that exists)
+    pub fn location(&self) -> String {
+        match self.member() {
+            Some(m) => {
+                let depth = m.pointer.matches('/').count();
+                let doc = self.path.ancestors().nth(depth).unwrap();
+                format!("{}#{}", doc.to_string_lossy(), m.pointer)
+            }
+            None => self.path.to_string_lossy().to_string(),
+        }
+    }
+
     pub fn cmp_by(&self, other: &Node, by: Sorting) -> Ordering {
         if by.dirs_first {
-            match (&self.info, &other.info) {
-                (NodeInfo::Dir { .. }, NodeInfo::Dir { .. }) => (),
-                (NodeInfo::Dir { .. }, _) => return Ordering::Less,
-                (_, NodeInfo::Dir { .. }) => return Ordering::Greater,
+            match (self.is_dir(), other.is_dir()) {
+                (true, false) => return Ordering::Less,
+                (false, true) => return Ordering::Greater,
                 _ => (),
             }
         }
 
+        // (entries in an archive only have a header)
+        let headers = (&self.header(), &other.header());
         match by.prop {
             SortingProp::None => Ordering::Equal,
-            SortingProp::Name => self.file_name().cmp(other.file_name()),
-            SortingProp::Size => cmp_in(&self.meta, &other.meta, Metadata::len),
+            SortingProp::Name => match (self.member(), other.member()) {
+                // indices in arrays
+                (Some(_), Some(_)) => {
+                    let index = |node: &Node| node.file_name().parse::<usize>().ok();
+                    match (index(self), index(other)) {
+                        (Some(a), Some(b)) => a.cmp(&b),
+                        _ => self.file_name().cmp(other.file_name()),
+                    }
+                }
+                _ => self.file_name().cmp(other.file_name()),
+            },
+            SortingProp::Size => cmp_in(&self.meta, &other.meta, |m| m.len)
+                .then_with(|| cmp_in(headers.0, headers.1, |h| h.size)),
             SortingProp::Extension => match (self.extension(), other.extension()) {
                 (Some(a), Some(b)) => a.cmp(b),
                 _ => self.file_name().cmp(other.file_name()),
             },
-            SortingProp::ATime => cmp_in(&self.meta, &other.meta, |m| m.accessed().unwrap()),
-            SortingProp::MTime => cmp_in(&self.meta, &other.meta, |m| m.modified().unwrap()),
-            SortingProp::CTime => cmp_in(&self.meta, &other.meta, |m| m.created().unwrap()),
+            SortingProp::ATime => cmp_in(&self.meta, &other.meta, |m| m.accessed)
+                .then_with(|| cmp_in(headers.0, headers.1, |h| h.mtime)),
+            SortingProp::MTime => cmp_in(&self.meta, &other.meta, |m| m.modified)
+                .then_with(|| cmp_in(headers.0, headers.1, |h| h.mtime)),
+            SortingProp::CTime => cmp_in(&self.meta, &other.meta, |m| m.created)
+                .then_with(|| cmp_in(headers.0, headers.1, |h| h.mtime)),
+        }
+    }
+
+    /// for an entry in an archive
+    fn header(&self) -> Option<Header> {
+        match self.info {
+            NodeInfo::Archive { header, .. } => header,
+            _ => None,
+        }
+    }
+
+    /// for a value in a document
+    pub fn member(&self) -> Option<&Member> {
+        match &self.info {
+            NodeInfo::Document { member, .. } => member.as_ref(),
+            _ => None,
         }
     }
 
@@ -474,8 +997,24 @@ impl Node {
         match &self.info {
             NodeInfo::Dir { loaded, children } if *loaded => Some(children),
 
+            NodeInfo::Listed { children } => Some(children),
+
             NodeInfo::Link { target: Ok(target) } => target.loaded_children(),
 
+            NodeInfo::Archive {
+                header,
+                loaded,
+                children,
+            } if *loaded && header.is_none_or(|h| h.dir) => Some(children),
+
+            NodeInfo::Document {
+                member,
+                loaded,
+                children,
+            } if *loaded && member.as_ref().is_none_or(|m| !m.is_scalar()) => {
+                Some(children)
+            }
+
             _ => None,
         }
     }
@@ -484,8 +1023,24 @@ impl Node {
         match &mut self.info {
             NodeInfo::Dir { loaded, children } if *loaded => Some(children),
 
+            NodeInfo::Listed { children } => Some(children),
+
             NodeInfo::Link { target: Ok(target) } => target.loaded_children_mut(),
 
+            NodeInfo::Archive {
+                header,
+                loaded,
+                children,
+            } if *loaded && header.is_none_or(|h| h.dir) => Some(children),
+
+            NodeInfo::Document {
+                member,
+                loaded,
+                children,
+            } if *loaded && member.as_ref().is_none_or(|m| !m.is_scalar()) => {
+                Some(children)
+            }
+
             _ => None,
         }
     }
@@ -494,22 +1049,17 @@ impl Node {
         match &mut self.info {
             NodeInfo::Dir { loaded, children } => {
                 if !*loaded {
-                    *children = self
-                        .path
-                        .read_dir()?
-                        .filter_map(Result::ok)
-                        .map(|ent| ent.metadata().and_then(|meta| Node::new(ent.path(), meta)))
-                        .filter_map(Result::ok)
-                        .collect();
+                    *children = Node::read_dir(&self.source, &self.path)?;
                     *loaded = true;
                 }
                 Ok(children)
             }
 
+            NodeInfo::Listed { children } => Ok(children),
+
             NodeInfo::Link { target } => match target {
                 Ok(node) => node.load_children(),
-                Err(path) => Err(io::Error::new(
-                    io::ErrorKind::Other,
+                Err(path) => Err(io::Error::other(
                     format!(
                         "(NotADirectory) cannot unfold file at {}",
                         path.to_string_lossy()
@@ -517,33 +1067,114 @@ impl Node {
                 )),
             },
 
-            NodeInfo::File { .. } => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!(
+            // (archives and documents are read from the local file system,
+            // whatever the source, see `TreeSource`)
+            NodeInfo::Archive { header: None, loaded, children } => {
+                if !*loaded {
+                    let entries = archive::list(&self.path)?;
+                    *children = archive_children(&self.source, &self.path, entries);
+                    *loaded = true;
+                }
+                Ok(children)
+            }
+
+            NodeInfo::Archive { header: Some(header), children, .. } if header.dir => Ok(children),
+
+            NodeInfo::Document { member: None, loaded, children } => {
+                if !*loaded {
+                    let value = document::read(&self.path)?;
+                    *children = document_children(&self.source, &self.path, "", &value);
+                    *loaded = true;
+                }
+                Ok(children)
+            }
+
+            NodeInfo::Document { member: Some(member), children, .. }
+                if !member.is_scalar() =>
+            {
+                Ok(children)
+            }
+
+            NodeInfo::File { .. } | NodeInfo::Archive { .. } | NodeInfo::Document { .. } => {
+                Err(io::Error::other(format!(
                     "(NotADirectory) cannot unfold file at {}",
                     self.path.to_string_lossy()
-                ),
-            )),
+                )))
+            }
         }
     }
 
     pub fn meta_to_string(&self) -> String {
-        match &self.meta {
-            Some(meta) => meta_to_string(meta),
-            None => "- no meta - ".to_string(),
+        match (&self.meta, self.header()) {
+            (Some(meta), _) => meta_to_string(meta),
+            (None, Some(h)) => {
+                format!("{}{}", if h.dir { 'd' } else { '-' }, mode_to_string(h.mode))
+            }
+            (None, None) => "- no meta - ".to_string(),
+        }
+    }
+
+    pub fn size_to_string(&self, format: &MetaFormat) -> String {
+        match (&self.meta, self.header()) {
+            (Some(meta), _) => size_to_string(meta.len, format.size),
+            (None, Some(h)) => size_to_string(h.size, format.size),
+            (None, None) => "-".to_string(),
+        }
+    }
+
+    /// `sel` picks one of `Meta::{accessed,modified,created}`
+    /// (entries in an archive only have their mtime, for all)
+    pub fn time_to_string(
+        &self,
+        sel: fn(&Meta) -> Option<SystemTime>,
+        format: &MetaFormat,
+    ) -> String {
+        match (self.meta.as_ref().map(sel), self.header()) {
+            (Some(Some(time)), _) => time_to_string(time, format.time),
+            (None, Some(h)) => {
+                let time = match u64::try_from(h.mtime) {
+                    Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+                    Err(_) => UNIX_EPOCH - Duration::from_secs(h.mtime.unsigned_abs()),
+                };
+                time_to_string(time, format.time)
+            }
+            _ => "-".to_string(),
         }
     }
 
     pub fn is_dir(&self) -> bool {
-        matches!(self.info, NodeInfo::Dir { .. })
+        match &self.info {
+            NodeInfo::Dir { .. } | NodeInfo::Listed { .. } => true,
+            NodeInfo::Archive { header, .. } => header.is_some_and(|h| h.dir),
+            NodeInfo::Document { member, .. } => member
+                .as_ref()
+                .is_some_and(|m| !m.is_scalar()),
+            _ => false,
+        }
     }
 
     pub fn is_link(&self) -> bool {
         matches!(self.info, NodeInfo::Link { .. })
     }
 
+    /// a directory, or a link to one
+    pub fn is_dir_through(&self) -> bool {
+        match &self.info {
+            NodeInfo::Link { target: Ok(node) } => node.is_dir_through(),
+            _ => self.is_dir(),
+        }
+    }
+
+    /// (an archive is a file, even though it can be unfolded)
     pub fn is_file(&self) -> bool {
-        matches!(self.info, NodeInfo::File { .. })
+        match &self.info {
+            NodeInfo::File { .. } => true,
+            NodeInfo::Archive { header, .. } => header.is_none_or(|h| !h.dir),
+            NodeInfo::Document { member, .. } => member
+                .as_ref()
+                .is_none_or(|m| m.is_scalar()),
+            _ => false,
+        }
     }
 
     pub fn file_name(&self) -> &str {
@@ -556,7 +1187,7 @@ impl Node {
 
     pub fn decoration(&self) -> String {
         match &self.info {
-            NodeInfo::Dir { .. } => "/".to_string(),
+            NodeInfo::Dir { .. } | NodeInfo::Listed { .. } => "/".to_string(),
             NodeInfo::Link { target } => match target {
                 Ok(node) => format!("@ -> {}{}", node.path.to_string_lossy(), node.decoration()),
                 Err(path) => format!("@ ~> {}", path.to_string_lossy()),
@@ -568,14 +1199,263 @@ impl Node {
                 _ => "",
             }
             .to_string(),
+            NodeInfo::Archive { header, .. } => {
+                if header.is_some_and(|h| h.dir) { "/" } else { "" }.to_string()
+            }
+            NodeInfo::Document { member, .. } => match member.as_ref().map(|m| &m.kind) {
+                Some(Kind::Object) => "{}".to_string(),
+                Some(Kind::Array) => "[]".to_string(),
+                Some(Kind::Scalar(value)) => format!(": {value}"),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// type of the content (for links, of the target)
+    pub fn magic(&self) -> Magic {
+        *self.magic.get_or_init(|| match &self.info {
+            NodeInfo::Dir { .. } | NodeInfo::Listed { .. } => magic::DIRECTORY,
+            NodeInfo::Link { target: Ok(node) } => node.magic(),
+            NodeInfo::Link { target: Err(_) } => magic::SYMLINK,
+            NodeInfo::File { kind } => match kind {
+                FileKind::NamedPipe => magic::FIFO,
+                FileKind::Socket => magic::SOCKET,
+                FileKind::CharDevice | FileKind::BlockDevice => magic::DEVICE,
+                FileKind::Regular | FileKind::Executable => {
+                    magic::detect(self.source.as_ref(), &self.path).unwrap_or(magic::BINARY)
+                }
+            },
+            NodeInfo::Archive { header: None, .. } => {
+                magic::detect(self.source.as_ref(), &self.path).unwrap_or(magic::BINARY)
+            }
+            NodeInfo::Archive { header: Some(h), .. } if h.dir => magic::DIRECTORY,
+            NodeInfo::Archive { .. } => magic::detect_name(&self.path),
+            NodeInfo::Document { member: None, .. } => {
+                magic::detect(self.source.as_ref(), &self.path).unwrap_or(magic::TEXT)
+            }
+            NodeInfo::Document { member: Some(m), .. } if m.is_scalar() => magic::TEXT,
+            NodeInfo::Document { .. } => magic::DIRECTORY,
+        })
+    }
+
+    /// as `LsColors` would find it (but from any source)
+    fn indicator(&self) -> Indicator {
+        let Some(meta) = &self.meta else { return Indicator::RegularFile; };
+        match meta.kind {
+            EntryKind::Dir => match (meta.mode & 0o1000 != 0, meta.mode & 0o002 != 0) {
+                (true, true) => Indicator::StickyAndOtherWritable,
+                (false, true) => Indicator::OtherWritable,
+                (true, false) => Indicator::Sticky,
+                (false, false) => Indicator::Directory,
+            },
+            // (the target of a relative link is from where the link is)
+            EntryKind::Symlink if self.source.metadata(&self.path).is_err() => {
+                Indicator::OrphanedSymbolicLink
+            }
+            EntryKind::Symlink => Indicator::SymbolicLink,
+            EntryKind::Fifo => Indicator::FIFO,
+            EntryKind::Socket => Indicator::Socket,
+            EntryKind::CharDevice => Indicator::CharacterDevice,
+            EntryKind::BlockDevice => Indicator::BlockDevice,
+            EntryKind::File if meta.mode & 0o4000 != 0 => Indicator::Setuid,
+            EntryKind::File if meta.mode & 0o2000 != 0 => Indicator::Setgid,
+            EntryKind::File if meta.mode & 0o111 != 0 => Indicator::ExecutableFile,
+            EntryKind::File => Indicator::RegularFile,
         }
     }
 
     pub fn style(&self) -> Style {
         let ls_colors = LsColors::from_env().unwrap_or_default();
-        ls_colors
-            .style_for_path_with_metadata(&self.path, self.meta.as_ref())
-            .map(lscolors_to_tui_style)
-            .unwrap_or_else(Style::default)
+        match (self.header(), self.member()) {
+            (Some(h), _) if h.dir => ls_colors.style_for_indicator(Indicator::Directory),
+            (_, Some(m)) if !m.is_scalar() => ls_colors.style_for_indicator(Indicator::Directory),
+            // (would be colored from the key's "extension")
+            (_, Some(_)) => None,
+            (None, None) if self.meta.is_none() => match self.info {
+                NodeInfo::Listed { .. } => ls_colors.style_for_indicator(Indicator::Directory),
+                _ => ls_colors.style_for_indicator(Indicator::MissingFile),
+            },
+            _ => match self.indicator() {
+                // (from the suffix when there is one)
+                Indicator::RegularFile => ls_colors.style_for_path_with_metadata(&self.path, None),
+                indicator => ls_colors.style_for_indicator(indicator),
+            },
+        }
+        .map(lscolors_to_tui_style)
+        .or_else(|| match self.info {
+            NodeInfo::File { .. } | NodeInfo::Archive { .. } => self.magic().style(),
+            NodeInfo::Document { member: None, .. } => self.magic().style(),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_to_string() {
+        assert_eq!(size_to_string(1536, SizeFormat::Bytes), "1536");
+        assert_eq!(size_to_string(12, SizeFormat::Iec), "12B");
+        assert_eq!(size_to_string(1536, SizeFormat::Iec), "1.5KiB");
+        assert_eq!(size_to_string(1536, SizeFormat::Si), "1.5kB");
+        assert_eq!(size_to_string(42 << 20, SizeFormat::Iec), "42MiB");
+    }
+
+    #[test]
+    fn test_time_to_string() {
+        assert_eq!(secs_to_iso(0), "1970-01-01T00:00:00Z");
+        assert_eq!(secs_to_iso(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(secs_to_iso(1700000000), "2023-11-14T22:13:20Z");
+        assert_eq!(secs_to_relative(3 * 3600 + 5), "3h ago");
+        assert_eq!(secs_to_relative(-90), "in 1m");
+    }
+
+    #[test]
+    fn test_memory_source() {
+        let mem = Rc::new(source::Memory::default());
+        mem.file("/r/a/b", 1536);
+        mem.dir("/r/c");
+        mem.link("/r/d", "/r/a");
+        let source: Rc<dyn TreeSource> = mem.clone();
+        let mut root = Node::new_root_in(&source, PathBuf::from("/r")).unwrap();
+        let names = |node: &Node| {
+            let mut r = node
+                .loaded_children()
+                .unwrap()
+                .iter()
+                .map(|ch| format!("{}{}", ch.file_name(), ch.decoration()))
+                .collect::<Vec<_>>();
+            r.sort();
+            r
+        };
+
+        root.load_children().unwrap()[0].load_children().unwrap();
+        assert_eq!(names(&root), ["a/", "c/", "d@ -> /r/a/"]);
+        let a = &root.loaded_children().unwrap()[0];
+        assert_eq!(a.loaded_children().unwrap()[0].meta_to_string(), "-rw-r--r--");
+
+        mem.remove("/r/c");
+        mem.remove("/r/d");
+        mem.file("/r/e", 0);
+        let root = root.renew().unwrap();
+        assert_eq!(names(&root), ["a/", "e"]);
+        // still loaded, from the same source
+        assert_eq!(names(&root.loaded_children().unwrap()[0]), ["b"]);
+    }
+
+    #[test]
+    fn test_archive_children() {
+        let entry = |path: &str, dir, mtime| archive::Entry {
+            path: PathBuf::from(path),
+            header: Header {
+                dir,
+                mtime,
+                ..Header::default()
+            },
+        };
+        // `x` and `x/y` are made up for `x/y/z`, then `x` is listed
+        let entries = vec![entry("x/y/z", false, 1), entry("w", false, 2), entry("x", true, 3)];
+        let chs = archive_children(&source::local(), Path::new("/a.tar"), entries);
+        let header = |node: &Node| match &node.info {
+            NodeInfo::Archive { header: Some(header), .. } => *header,
+            _ => panic!("not an archive entry"),
+        };
+
+        assert_eq!(chs.iter().map(Node::file_name).collect::<Vec<_>>(), ["x", "w"]);
+        let x = &chs[0];
+        assert_eq!(x.as_path(), Path::new("/a.tar/x"));
+        assert!(header(x).dir);
+        assert_eq!(header(x).mtime, 3);
+        let y = &x.loaded_children().unwrap()[0];
+        assert!(header(y).dir);
+        assert_eq!(header(y).mtime, 0);
+        let z = &y.loaded_children().unwrap()[0];
+        assert_eq!(z.as_path(), Path::new("/a.tar/x/y/z"));
+        assert!(!header(z).dir);
+    }
+
+    #[test]
+    fn test_renew_archive() {
+        let dir = crate::testdir::TestDir::new("renew-archive");
+        let path = dir.join("a.tar");
+        // same size whatever the name, and always the same time
+        let write = |name: &str| {
+            let mut tar = tar::Builder::new(std::fs::File::create(&path).unwrap());
+            let mut header = tar::Header::new_gnu();
+            header.set_size(0);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            tar.append_data(&mut header, name, io::empty()).unwrap();
+            tar.into_inner()
+                .unwrap()
+                .set_modified(UNIX_EPOCH + Duration::from_secs(1700000000))
+                .unwrap();
+        };
+        let names = |root: &Node| {
+            root.loaded_children().unwrap()[0]
+                .loaded_children()
+                .unwrap()
+                .iter()
+                .map(|ch| ch.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        write("aa");
+        let mut root = Node::new_root(dir.to_path_buf()).unwrap();
+        root.load_children().unwrap()[0].load_children().unwrap();
+        assert_eq!(names(&root), ["aa"]);
+
+        // not read again when it looks the same
+        write("bb");
+        let root = root.renew().unwrap();
+        assert_eq!(names(&root), ["aa"]);
+
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now())
+            .unwrap();
+        let root = root.renew().unwrap();
+        assert_eq!(names(&root), ["bb"]);
+    }
+
+    #[test]
+    fn test_renew_document() {
+        let dir = crate::testdir::TestDir::new("renew-document");
+        let path = dir.join("a.json");
+        let write = |content: &str| {
+            std::fs::write(&path, content).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + Duration::from_secs(1700000000))
+                .unwrap();
+        };
+        let names = |root: &Node| {
+            root.loaded_children()
+                .unwrap()
+                .iter()
+                .map(|ch| ch.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        write(r#"{"aa": 1}"#);
+        let mut root = Node::new_root(path.clone()).unwrap();
+        root.load_children().unwrap();
+        assert_eq!(names(&root), ["aa"]);
+
+        // not parsed again when it looks the same
+        write(r#"{"bb": 1}"#);
+        let root = root.renew().unwrap();
+        assert_eq!(names(&root), ["aa"]);
+
+        write(r#"{"bb": 12}"#);
+        let root = root.renew().unwrap();
+        assert_eq!(names(&root), ["bb"]);
     }
 }
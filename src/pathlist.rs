@@ -0,0 +1,103 @@
+// arbitrary lists of paths (eg. `git ls-files`), to be shown as a tree
+// of only these paths, like `tree --fromfile`
+use std::{
+    borrow::Cow,
+    io::{self, BufRead},
+    path::{Component, Path, PathBuf},
+};
+
+// the paths are as listed, not necessarily UTF-8
+#[cfg(unix)]
+fn bytes_path(bytes: &[u8]) -> Cow<'_, Path> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    Cow::Borrowed(Path::new(OsStr::from_bytes(bytes)))
+}
+
+#[cfg(not(unix))]
+fn bytes_path(bytes: &[u8]) -> Cow<'_, Path> {
+    Cow::Owned(PathBuf::from(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// `path` from `cwd`, without any `.` or `..` (not resolving links)
+fn absolute(cwd: &Path, path: &Path) -> PathBuf {
+    let mut r = PathBuf::new();
+    for comp in cwd.join(path).components() {
+        match comp {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                r.pop();
+            }
+            comp => r.push(comp),
+        }
+    }
+    r
+}
+
+/// one path per line, empty lines are ignored; relative
+/// paths are from `cwd`
+pub fn read(input: impl BufRead, cwd: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut r = Vec::new();
+    for line in input.split(b'\n') {
+        let line = line?;
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if !line.is_empty() {
+            r.push(absolute(cwd, &bytes_path(line)));
+        }
+    }
+    Ok(r)
+}
+
+/// the deepest directory containing every paths, or `cwd`
+/// if they all are under it
+pub fn root(paths: &[PathBuf], cwd: &Path) -> PathBuf {
+    // (`cwd` itself may be listed, eg. with `find .`)
+    if paths.iter().all(|it| it.starts_with(cwd)) {
+        return cwd.to_path_buf();
+    }
+    let mut r = paths[0].parent().unwrap_or(&paths[0]).to_path_buf();
+    for it in paths {
+        while !it.starts_with(&r) {
+            r.pop();
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_root() {
+        let cwd = Path::new("/home/me");
+        let paths = read(".\na/b\n./c\r\n\n../me/d/../e\n".as_bytes(), cwd).unwrap();
+        assert_eq!(
+            paths,
+            ["/home/me", "/home/me/a/b", "/home/me/c", "/home/me/e"].map(PathBuf::from)
+        );
+        assert_eq!(root(&paths, cwd), cwd);
+        let paths = ["/etc/a/b", "/etc/a/c/d"].map(PathBuf::from);
+        assert_eq!(root(&paths, cwd), Path::new("/etc/a"));
+        assert_eq!(root(&[], cwd), cwd);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let paths = read(&b"a\xff\n"[..], cwd).unwrap();
+            assert_eq!(paths[0].file_name().unwrap().as_bytes(), b"a\xff");
+        }
+    }
+
+    #[test]
+    fn test_only_listed() {
+        let dir = crate::testdir::TestDir::new("pathlist");
+        std::fs::write(dir.join("a"), "").unwrap();
+        // (the root itself, or nothing, is not the whole directory)
+        for paths in [vec![], vec![dir.to_path_buf()]] {
+            let root = crate::node::Node::from_paths(dir.to_path_buf(), paths);
+            assert_eq!(0, root.loaded_children().unwrap().len());
+        }
+        let root = crate::node::Node::from_paths(dir.to_path_buf(), vec![dir.join("a")]);
+        assert_eq!(1, root.loaded_children().unwrap().len());
+    }
+}
@@ -0,0 +1,268 @@
+#[cfg(unix)]
+use std::ffi::{CStr, CString};
+use std::{fs, io, path::Path};
+
+/// resulting mode when applying `spec`, either octal (`644`) or
+/// symbolic (`u+x,go-w`), to `mode`; like chmod(1) but without
+/// considering the umask
+pub fn parse_mode(spec: &str, mode: u32, is_dir: bool) -> Result<u32, String> {
+    if !spec.is_empty() && spec.len() <= 4 && spec.chars().all(|c| c.is_digit(8)) {
+        let val = u32::from_str_radix(spec, 8).unwrap();
+        return Ok(mode & !0o7777 | val);
+    }
+
+    let mut mode = mode;
+    for clause in spec.split(',') {
+        let ops_at = clause
+            .find(['+', '-', '='])
+            .ok_or_else(|| format!("invalid mode '{clause}' (missing one of +-=)"))?;
+        let (who, mut rest) = clause.split_at(ops_at);
+
+        let (mut bits_for, mut mask) = (0, 0);
+        for c in if who.is_empty() { "a".chars() } else { who.chars() } {
+            let (b, m) = match c {
+                'u' => (0o4700, 0o4700),
+                'g' => (0o2070, 0o2070),
+                'o' => (0o1007, 0o0007),
+                'a' => (0o7777, 0o7777),
+                _ => return Err(format!("invalid mode '{clause}' (unknown '{c}')")),
+            };
+            bits_for |= b;
+            mask |= m;
+        }
+
+        while let Some(op) = rest.chars().next() {
+            let perms_end = rest[1..].find(['+', '-', '=']).map_or(rest.len(), |k| k + 1);
+            let perms = &rest[1..perms_end];
+            rest = &rest[perms_end..];
+
+            let mut bits = 0;
+            for c in perms.chars() {
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    'X' if is_dir || 0 != mode & 0o111 => 0o111,
+                    'X' => 0,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    _ => return Err(format!("invalid mode '{clause}' (unknown '{c}')")),
+                };
+            }
+            bits &= bits_for;
+
+            mode = match op {
+                '+' => mode | bits,
+                '-' => mode & !bits,
+                _ => mode & !mask | bits,
+            };
+        }
+    }
+
+    Ok(mode)
+}
+
+fn lookup_id(name: &str, what: &str, by_name: fn(&str) -> Option<u32>) -> Result<u32, String> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    by_name(name).ok_or_else(|| format!("unknown {what} '{name}'"))
+}
+
+// calls `f` with a buffer for the reentrant getpw*_r/getgr*_r,
+// growing it for as long as it is too small
+#[cfg(unix)]
+fn with_buffer<T>(f: impl Fn(&mut [libc::c_char]) -> (libc::c_int, Option<T>)) -> Option<T> {
+    let mut buf = vec![0; 1024];
+    loop {
+        match f(&mut buf) {
+            (libc::ERANGE, _) if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            (_, r) => return r,
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn user_name(uid: u32) -> Option<String> {
+    // SAFETY: passwd is plain data (zeroed is valid), the buffer outlives the call,
+    // and pw_* only point into it when `found` is set
+    with_buffer(|buf| unsafe {
+        let mut pwd = std::mem::zeroed::<libc::passwd>();
+        let mut found = std::ptr::null_mut();
+        let rc = libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut found);
+        let name = (!found.is_null())
+            .then(|| CStr::from_ptr(pwd.pw_name).to_string_lossy().into_owned());
+        (rc, name)
+    })
+}
+
+#[cfg(unix)]
+pub fn group_name(gid: u32) -> Option<String> {
+    // SAFETY: group is plain data (zeroed is valid), the buffer outlives the call,
+    // and gr_* only point into it when `found` is set
+    with_buffer(|buf| unsafe {
+        let mut grp = std::mem::zeroed::<libc::group>();
+        let mut found = std::ptr::null_mut();
+        let rc = libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut found);
+        let name = (!found.is_null())
+            .then(|| CStr::from_ptr(grp.gr_name).to_string_lossy().into_owned());
+        (rc, name)
+    })
+}
+
+#[cfg(unix)]
+fn user_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    // SAFETY: passwd is plain data (zeroed is valid), the buffer outlives the call,
+    // and pw_* only point into it when `found` is set
+    with_buffer(|buf| unsafe {
+        let mut pwd = std::mem::zeroed::<libc::passwd>();
+        let mut found = std::ptr::null_mut();
+        let rc = libc::getpwnam_r(name.as_ptr(), &mut pwd, buf.as_mut_ptr(), buf.len(), &mut found);
+        (rc, (!found.is_null()).then_some(pwd.pw_uid))
+    })
+}
+
+#[cfg(unix)]
+fn group_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    // SAFETY: group is plain data (zeroed is valid), the buffer outlives the call,
+    // and gr_* only point into it when `found` is set
+    with_buffer(|buf| unsafe {
+        let mut grp = std::mem::zeroed::<libc::group>();
+        let mut found = std::ptr::null_mut();
+        let rc = libc::getgrnam_r(name.as_ptr(), &mut grp, buf.as_mut_ptr(), buf.len(), &mut found);
+        (rc, (!found.is_null()).then_some(grp.gr_gid))
+    })
+}
+
+/// user and/or group ids from `user`, `user:group` or `:group`
+pub fn parse_owner(spec: &str) -> Result<(Option<u32>, Option<u32>), String> {
+    let (user, group) = spec.split_once(':').unwrap_or((spec, ""));
+    let uid = (!user.is_empty())
+        .then(|| lookup_id(user, "user", user_id))
+        .transpose()?;
+    let gid = (!group.is_empty())
+        .then(|| lookup_id(group, "group", group_id))
+        .transpose()?;
+    if uid.is_none() && gid.is_none() {
+        return Err(format!("invalid owner '{spec}'"));
+    }
+    Ok((uid, gid))
+}
+
+/// call `f` on `path` and, if `recursive`, everything within it;
+/// links found within are skipped, as `f` would change what they
+/// point to (which could be anywhere); @ret the errors, if any
+pub fn walk(path: &Path, recursive: bool, f: &impl Fn(&Path) -> io::Result<()>) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Err(err) = f(path) {
+        errors.push(format!("{}: {err}", path.to_string_lossy()));
+    }
+    if recursive && fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir()) {
+        match path.read_dir() {
+            Ok(ents) => {
+                for ent in ents.filter_map(Result::ok) {
+                    if !ent.file_type().is_ok_and(|ft| ft.is_symlink()) {
+                        errors.extend(walk(&ent.path(), true, f));
+                    }
+                }
+            }
+            Err(err) => errors.push(format!("{}: {err}", path.to_string_lossy())),
+        }
+    }
+    errors
+}
+
+#[cfg(unix)]
+pub fn mode_of(path: &Path) -> io::Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode())
+}
+
+#[cfg(unix)]
+pub fn set_mode(path: &Path, spec: &str) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = parse_mode(spec, mode_of(path)?, path.is_dir()).map_err(io::Error::other)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))
+}
+
+#[cfg(unix)]
+pub fn set_owner(path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    std::os::unix::fs::chown(path, uid, gid)
+}
+
+#[cfg(windows)]
+pub fn mode_of(_path: &Path) -> io::Result<u32> {
+    Err(io::Error::other("not supported on this platform"))
+}
+
+#[cfg(windows)]
+pub fn set_mode(_path: &Path, _spec: &str) -> io::Result<()> {
+    Err(io::Error::other("not supported on this platform"))
+}
+
+#[cfg(windows)]
+pub fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
+pub fn group_name(_gid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
+fn user_id(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(windows)]
+fn group_id(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(windows)]
+pub fn set_owner(_path: &Path, _uid: Option<u32>, _gid: Option<u32>) -> io::Result<()> {
+    Err(io::Error::other("not supported on this platform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_mode, walk};
+    use crate::testdir::TestDir;
+    use std::{cell::RefCell, fs};
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("644", 0o100755, false), Ok(0o100644));
+        assert_eq!(parse_mode("u+x,go-w", 0o666, false), Ok(0o744));
+        assert_eq!(parse_mode("a=r", 0o777, false), Ok(0o444));
+        assert_eq!(parse_mode("+X", 0o644, true), Ok(0o755));
+        assert_eq!(parse_mode("+X", 0o644, false), Ok(0o644));
+        assert_eq!(parse_mode("u=rw,g=r,o=", 0o777, false), Ok(0o640));
+        assert_eq!(parse_mode("u+s-w", 0o755, false), Ok(0o4555));
+        assert!(parse_mode("z+x", 0o644, false).is_err());
+        assert!(parse_mode("ux", 0o644, false).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk() {
+        let dir = TestDir::new("walk");
+        let outside = TestDir::new("walk-outside");
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/file"), "").unwrap();
+        std::os::unix::fs::symlink(&*outside, dir.join("sub/link")).unwrap();
+
+        let seen = RefCell::new(Vec::new());
+        let errors = walk(&dir, true, &|path| {
+            seen.borrow_mut().push(path.strip_prefix(&*dir).unwrap().to_path_buf());
+            Ok(())
+        });
+        assert!(errors.is_empty());
+        let mut seen = seen.into_inner();
+        seen.sort();
+        assert_eq!(seen, ["", "sub", "sub/file"].map(std::path::PathBuf::from));
+    }
+}
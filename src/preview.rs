@@ -0,0 +1,410 @@
+use crate::{
+    highlight::{highlight, Syntax},
+    magic::is_binary,
+    node::Node,
+    source::{EntryKind, TreeSource},
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+/// how much of a file is read to be previewed
+const PREVIEW_BYTES: u64 = 64 * 1024;
+/// past this, text is not highlighted (the lines after are plain)
+const HIGHLIGHT_BYTES: usize = 32 * 1024;
+const TAB_WIDTH: usize = 8;
+/// bytes per row, and per read, of a hex dump
+const HEX_WIDTH: usize = 16;
+const HEX_BLOCK: u64 = 64 * 1024;
+
+/// what a file that is neither a directory nor a regular file is
+fn special_kind(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::Fifo => "fifo",
+        EntryKind::Socket => "socket",
+        _ => "device",
+    }
+}
+
+/// tabs expanded and other control characters made visible
+fn sanitize(line: &str) -> String {
+    let mut r = String::with_capacity(line.len());
+    let mut col = 0;
+    for ch in line.chars() {
+        match ch {
+            '\t' => {
+                let n = TAB_WIDTH - col % TAB_WIDTH;
+                r.push_str(&" ".repeat(n));
+                col += n;
+            }
+            '\r' => (),
+            c if c.is_ascii_control() => {
+                r.push('^');
+                r.push(((c as u8) ^ 0x40) as char);
+                col += 2;
+            }
+            c if c.is_control() => {
+                r.push('\u{fffd}');
+                col += 1;
+            }
+            c => {
+                r.push(c);
+                col += 1;
+            }
+        }
+    }
+    r
+}
+
+/// highlighted up to the last line that starts within `HIGHLIGHT_BYTES`
+fn text_lines(syntax: Option<Syntax>, text: &str) -> Vec<Spans<'static>> {
+    let cut = match syntax {
+        None => 0,
+        Some(_) if text.len() <= HIGHLIGHT_BYTES => text.len(),
+        Some(_) => text.as_bytes()[..HIGHLIGHT_BYTES]
+            .iter()
+            .rposition(|b| b'\n' == *b)
+            .map_or(0, |at| at + 1),
+    };
+    let mut r = syntax.map_or_else(Vec::new, |syntax| highlight(syntax, &text[..cut], sanitize));
+    r.extend(text[cut..].lines().map(|line| Spans::from(sanitize(line))));
+    r
+}
+
+fn listing(source: &Rc<dyn TreeSource>, path: &Path) -> io::Result<Vec<Spans<'static>>> {
+    let mut nodes = source
+        .read_dir(path)?
+        .into_iter()
+        .filter_map(|(path, meta)| Node::new_in(source, path, meta).ok())
+        .collect::<Vec<_>>();
+    nodes.sort_unstable_by(|a, b| {
+        b.is_dir()
+            .cmp(&a.is_dir())
+            .then_with(|| a.file_name().cmp(b.file_name()))
+    });
+
+    let dirs = nodes.iter().filter(|it| it.is_dir()).count();
+    let files = nodes.len() - dirs;
+    let header = format!(
+        "{dirs} director{}, {files} file{}",
+        if 1 == dirs { "y" } else { "ies" },
+        if 1 == files { "" } else { "s" }
+    );
+
+    Ok(std::iter::once(Spans::from(Span::styled(
+        header,
+        Style::default().fg(Color::DarkGray),
+    )))
+    .chain(nodes.iter().map(|node| {
+        Spans::from(vec![
+            Span::styled(sanitize(node.file_name()), node.style()),
+            Span::raw(node.decoration()),
+        ])
+    }))
+    .collect())
+}
+
+/// a file read lazily, one block at a time, to be shown as a
+/// hex dump (`hexdump -C`-like)
+struct HexDump {
+    source: Rc<dyn TreeSource>,
+    path: PathBuf,
+    len: u64,
+    /// offset of, and what is currently loaded
+    at: u64,
+    bytes: Vec<u8>,
+}
+
+impl HexDump {
+    fn new(source: &Rc<dyn TreeSource>, path: &Path) -> io::Result<HexDump> {
+        let len = source.metadata(path)?.len;
+        Ok(HexDump {
+            source: Rc::clone(source),
+            path: path.to_path_buf(),
+            len,
+            at: 0,
+            bytes: Vec::new(),
+        })
+    }
+
+    fn rows(&self) -> usize {
+        self.len.div_ceil(HEX_WIDTH as u64) as usize
+    }
+
+    /// make sure `rows` rows from `first` are loaded
+    fn load(&mut self, first: usize, rows: usize) -> io::Result<()> {
+        let start = (first * HEX_WIDTH) as u64;
+        let end = (((first + rows) * HEX_WIDTH) as u64).min(self.len);
+        if self.at <= start && end <= self.at + self.bytes.len() as u64 {
+            return Ok(());
+        }
+        let at = start - start % HEX_BLOCK;
+        let end = end.div_ceil(HEX_BLOCK) * HEX_BLOCK;
+        self.bytes = self.source.read_at(&self.path, at, end - at)?;
+        self.at = at;
+        Ok(())
+    }
+
+    fn row(&self, row: usize) -> Option<Spans<'static>> {
+        let offset = (row * HEX_WIDTH) as u64;
+        let from = offset.checked_sub(self.at)? as usize;
+        let bytes = self.bytes.get(from..)?;
+        let bytes = &bytes[..bytes.len().min(HEX_WIDTH)];
+        if bytes.is_empty() {
+            return None;
+        }
+
+        let mut hex = String::with_capacity(3 * HEX_WIDTH + 1);
+        for k in 0..HEX_WIDTH {
+            if HEX_WIDTH / 2 == k {
+                hex.push(' ');
+            }
+            match bytes.get(k) {
+                Some(b) => hex += &format!("{b:02x} "),
+                None => hex += "   ",
+            }
+        }
+        let ascii = bytes
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b' ' == b {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+
+        let dim = Style::default().fg(Color::DarkGray);
+        Some(Spans::from(vec![
+            Span::styled(format!("{offset:08x}  "), dim),
+            Span::raw(hex),
+            Span::styled(" |", dim),
+            Span::raw(ascii),
+            Span::styled("|", dim),
+        ]))
+    }
+}
+
+enum Content {
+    Lines(Vec<Spans<'static>>),
+    Hex(HexDump),
+}
+
+impl Default for Content {
+    fn default() -> Content {
+        Content::Lines(Vec::new())
+    }
+}
+
+/// what is shown of the node at the cursor, next to the views
+#[derive(Default)]
+pub struct Preview {
+    path: PathBuf,
+    content: Content,
+    scroll: usize,
+}
+
+impl Preview {
+    fn load(source: &Rc<dyn TreeSource>, path: &Path) -> io::Result<Content> {
+        let meta = source.metadata(path)?;
+        if meta.is_dir() {
+            return listing(source, path).map(Content::Lines);
+        }
+        // opening a fifo would block (and a device, who knows)
+        if !meta.is_file() {
+            return Ok(Content::Lines(vec![Spans::from(Span::styled(
+                format!("({})", special_kind(meta.kind)),
+                Style::default().fg(Color::DarkGray),
+            ))]));
+        }
+        let block = source.read_at(path, 0, PREVIEW_BYTES)?;
+        if is_binary(&block) {
+            return HexDump::new(source, path).map(Content::Hex);
+        }
+        let text = String::from_utf8_lossy(&block);
+        let syntax = Syntax::detect(path, text.lines().next().unwrap_or(""));
+        Ok(Content::Lines(text_lines(syntax, &text)))
+    }
+
+    fn error(err: io::Error) -> Content {
+        Content::Lines(vec![Spans::from(Span::styled(
+            format!("{err}"),
+            Style::default().fg(Color::Red),
+        ))])
+    }
+
+    /// (re)load if the node at the cursor changed, and what
+    /// is needed for the next `height` lines to be shown
+    pub fn update(&mut self, source: &Rc<dyn TreeSource>, path: &Path, height: usize) {
+        if self.path != path {
+            self.path = path.to_path_buf();
+            self.scroll = 0;
+            self.content = Preview::load(source, path).unwrap_or_else(Preview::error);
+        }
+        if let Content::Hex(hex) = &mut self.content {
+            if let Err(err) = hex.load(self.scroll, height) {
+                self.content = Preview::error(err);
+            }
+        }
+    }
+
+    /// forget what was loaded, eg. after the file changed
+    pub fn invalidate(&mut self) {
+        self.path = PathBuf::new();
+    }
+
+    fn len(&self) -> usize {
+        match &self.content {
+            Content::Lines(lines) => lines.len(),
+            Content::Hex(hex) => hex.rows(),
+        }
+    }
+
+    pub fn scroll(&mut self, by: i32) {
+        let last = self.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(by as isize).min(last);
+    }
+}
+
+impl Widget for &Preview {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let title = self
+            .path
+            .file_name()
+            .map(|it| format!(" {} ", it.to_string_lossy()))
+            .unwrap_or_default();
+        let surround = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .title(title);
+        let inner = surround.inner(area);
+        surround.render(area, buf);
+
+        let height = inner.height as usize;
+        let mut put =
+            |k: usize, line: &Spans| buf.set_spans(inner.x, inner.y + k as u16, line, inner.width);
+        match &self.content {
+            Content::Lines(lines) => {
+                for (k, line) in lines.iter().skip(self.scroll).take(height).enumerate() {
+                    put(k, line);
+                }
+            }
+            Content::Hex(hex) => {
+                for k in 0..height {
+                    let Some(line) = hex.row(self.scroll + k) else {
+                        break;
+                    };
+                    put(k, &line);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        source::{self, Memory},
+        testdir::TestDir,
+    };
+    use std::fs;
+
+    #[test]
+    fn test_text_lines() {
+        let line = "let a = \"b\";\n";
+        let text = line.repeat(HIGHLIGHT_BYTES / line.len() + 10);
+        let lines = text_lines(Some(Syntax::Rust), &text);
+        assert_eq!(text.lines().count(), lines.len());
+        // highlighted then plain, each line whole
+        assert!(1 < lines[0].0.len());
+        assert_eq!(1, lines.last().unwrap().0.len());
+        let joined = |spans: &Spans| {
+            spans.0.iter().map(|it| it.content.as_ref()).collect::<String>()
+        };
+        assert!(lines.iter().all(|spans| joined(spans) == line.trim_end()));
+
+        assert_eq!(1, text_lines(None, line)[0].0.len());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("a\tb"), "a       b");
+        assert_eq!(sanitize("abcdefgh\tc"), "abcdefgh        c");
+        assert_eq!(sanitize("x\x1by\r"), "x^[y");
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let dir = TestDir::new("hex");
+        let path = dir.join("bin");
+        // one block and a row and a bit
+        let bytes = (0..HEX_BLOCK + 20).map(|k| k as u8).collect::<Vec<_>>();
+        fs::write(&path, bytes).unwrap();
+
+        let mut hex = HexDump::new(&source::local(), &path).unwrap();
+        let text = |hex: &HexDump, row| {
+            hex.row(row)
+                .map(|spans| spans.0.iter().map(|it| it.content.as_ref()).collect::<String>())
+        };
+        assert_eq!(4098, hex.rows());
+
+        // across the end of the first block
+        hex.load(4095, 2).unwrap();
+        assert_eq!(
+            text(&hex, 4095).unwrap(),
+            "0000fff0  f0 f1 f2 f3 f4 f5 f6 f7  f8 f9 fa fb fc fd fe ff  |................|"
+        );
+        assert_eq!(
+            text(&hex, 4096).unwrap(),
+            "00010000  00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f  |................|"
+        );
+
+        // already there
+        hex.load(4097, 1).unwrap();
+        assert_eq!(0, hex.at);
+
+        // only the second block is loaded then
+        let mut hex = HexDump::new(&source::local(), &path).unwrap();
+        hex.load(4097, 1).unwrap();
+        assert_eq!(HEX_BLOCK, hex.at);
+        assert_eq!(None, text(&hex, 4095));
+        assert_eq!(
+            text(&hex, 4097).unwrap(),
+            format!("00010010  10 11 12 13 {}  |....|", " ".repeat(3 * 12))
+        );
+        assert_eq!(None, text(&hex, 4098));
+    }
+
+    #[test]
+    fn test_load() {
+        let mem = Rc::new(Memory::default());
+        mem.text("/r/a.rs", "fn main() {}\n");
+        mem.file("/r/b", 20);
+        let source: Rc<dyn TreeSource> = mem;
+        let Content::Lines(lines) = Preview::load(&source, Path::new("/r")).unwrap() else {
+            panic!("not a listing");
+        };
+        assert_eq!(3, lines.len());
+        let Content::Lines(lines) = Preview::load(&source, Path::new("/r/a.rs")).unwrap() else {
+            panic!("not text");
+        };
+        assert_eq!(1, lines.len());
+        let Content::Hex(hex) = Preview::load(&source, Path::new("/r/b")).unwrap() else {
+            panic!("not a hex dump");
+        };
+        assert_eq!(2, hex.rows());
+    }
+}
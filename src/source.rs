@@ -0,0 +1,368 @@
+// where the nodes of a tree come from: the local file system, or
+// anything else that can list directories and describe entries
+use std::{
+    fmt, fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Dir,
+    File,
+    Symlink,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+}
+
+/// what a source knows of an entry (the `fs::Metadata` of any source)
+#[derive(Debug, Clone)]
+pub struct Meta {
+    pub kind: EntryKind,
+    pub len: u64,
+    /// permission bits, with setuid, setgid and sticky
+    pub mode: u32,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+}
+
+impl Meta {
+    pub fn is_dir(&self) -> bool {
+        EntryKind::Dir == self.kind
+    }
+
+    pub fn is_file(&self) -> bool {
+        EntryKind::File == self.kind
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        EntryKind::Symlink == self.kind
+    }
+}
+
+#[cfg(unix)]
+impl From<fs::Metadata> for Meta {
+    fn from(meta: fs::Metadata) -> Meta {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::fs::PermissionsExt;
+        let ft = meta.file_type();
+        Meta {
+            kind: if ft.is_dir() {
+                EntryKind::Dir
+            } else if ft.is_symlink() {
+                EntryKind::Symlink
+            } else if ft.is_fifo() {
+                EntryKind::Fifo
+            } else if ft.is_socket() {
+                EntryKind::Socket
+            } else if ft.is_char_device() {
+                EntryKind::CharDevice
+            } else if ft.is_block_device() {
+                EntryKind::BlockDevice
+            } else {
+                EntryKind::File
+            },
+            len: meta.len(),
+            mode: meta.permissions().mode() & 0o7777,
+            accessed: meta.accessed().ok(),
+            modified: meta.modified().ok(),
+            created: meta.created().ok(),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<fs::Metadata> for Meta {
+    fn from(meta: fs::Metadata) -> Meta {
+        let ft = meta.file_type();
+        Meta {
+            kind: if ft.is_dir() {
+                EntryKind::Dir
+            } else if ft.is_symlink() {
+                EntryKind::Symlink
+            } else {
+                EntryKind::File
+            },
+            len: meta.len(),
+            // (nothing to say a file is executable, nor who can write)
+            mode: match (ft.is_dir(), meta.permissions().readonly()) {
+                (true, true) => 0o555,
+                (true, false) => 0o755,
+                (false, true) => 0o444,
+                (false, false) => 0o644,
+            },
+            accessed: meta.accessed().ok(),
+            modified: meta.modified().ok(),
+            created: meta.created().ok(),
+        }
+    }
+}
+
+/// note that a few things are still only ever done on the local file
+/// system: reading the content of archives and documents (to browse them
+/// as directories), the details of `info` beyond `Meta` (inode, owner,
+/// extended attributes...), completing paths on the command line and
+/// every file operation (copy, move, delete, rename, chmod...)
+pub trait TreeSource: fmt::Debug {
+    /// following links
+    fn metadata(&self, path: &Path) -> io::Result<Meta>;
+    /// not following links
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Meta>;
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+    /// the entries of a directory, with their `symlink_metadata`;
+    /// those that cannot be read are left out
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Meta)>>;
+    /// the absolute path, with every link resolved
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    /// at most `len` bytes from `offset` in a regular file (anything
+    /// else is an error, without waiting on eg. a fifo)
+    fn read_at(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>>;
+    /// whether the local file system (which has more to say of an entry)
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+/// the local file system
+#[derive(Debug)]
+pub struct Local;
+
+impl TreeSource for Local {
+    fn metadata(&self, path: &Path) -> io::Result<Meta> {
+        fs::metadata(path).map(Meta::from)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Meta> {
+        fs::symlink_metadata(path).map(Meta::from)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Meta)>> {
+        Ok(fs::read_dir(path)?
+            .filter_map(Result::ok)
+            .filter_map(|ent| Some((ent.path(), ent.metadata().ok()?.into())))
+            .collect())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut options = fs::OpenOptions::new();
+        options.read(true);
+        // so that opening a fifo does not wait for a writer
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_NONBLOCK);
+        }
+        let mut file = options.open(path)?;
+        if !file.metadata()?.is_file() {
+            return Err(io::Error::other("not a regular file"));
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut block = Vec::new();
+        file.take(len).read_to_end(&mut block)?;
+        Ok(block)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+thread_local! {
+    static LOCAL: Rc<dyn TreeSource> = Rc::new(Local);
+}
+
+/// the one instance, shared by every node
+pub fn local() -> Rc<dyn TreeSource> {
+    LOCAL.with(Rc::clone)
+}
+
+/// entries only in memory, which can be changed after
+/// the tree was made (eg. for tests)
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct Memory {
+    entries: std::cell::RefCell<std::collections::BTreeMap<PathBuf, Entry>>,
+}
+
+/// what the entry is, where it points to (a link) and what it holds (a file)
+#[cfg(test)]
+type Entry = (Meta, Option<PathBuf>, Vec<u8>);
+
+#[cfg(test)]
+impl Memory {
+    fn put(&self, path: &str, kind: EntryKind, target: Option<PathBuf>, content: Vec<u8>) {
+        let meta = Meta {
+            kind,
+            len: content.len() as u64,
+            mode: if EntryKind::Dir == kind { 0o755 } else { 0o644 },
+            accessed: None,
+            modified: Some(SystemTime::now()),
+            created: None,
+        };
+        let path = PathBuf::from(path);
+        // (as with `mkdir -p`)
+        for parent in path.ancestors().skip(1) {
+            if !parent.as_os_str().is_empty() && !self.entries.borrow().contains_key(parent) {
+                self.put(parent.to_str().unwrap(), EntryKind::Dir, None, Vec::new());
+            }
+        }
+        self.entries
+            .borrow_mut()
+            .insert(path, (meta, target, content));
+    }
+
+    pub fn dir(&self, path: &str) {
+        self.put(path, EntryKind::Dir, None, Vec::new());
+    }
+
+    /// (filled with zeros)
+    pub fn file(&self, path: &str, len: u64) {
+        self.put(path, EntryKind::File, None, vec![0; len as usize]);
+    }
+
+    pub fn text(&self, path: &str, content: &str) {
+        self.put(path, EntryKind::File, None, content.into());
+    }
+
+    pub fn link(&self, path: &str, target: &str) {
+        self.put(path, EntryKind::Symlink, Some(target.into()), Vec::new());
+    }
+
+    /// with everything under it
+    pub fn remove(&self, path: &str) {
+        self.entries
+            .borrow_mut()
+            .retain(|it, _| !it.starts_with(path));
+    }
+}
+
+#[cfg(test)]
+impl TreeSource for Memory {
+    fn metadata(&self, path: &Path) -> io::Result<Meta> {
+        self.symlink_metadata(&self.canonicalize(path)?)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Meta> {
+        self.entries
+            .borrow()
+            .get(path)
+            .map(|(meta, _, _)| meta.clone())
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        match self.entries.borrow().get(path) {
+            Some((_, Some(target), _)) => Ok(target.clone()),
+            Some(_) => Err(io::ErrorKind::InvalidInput.into()),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Meta)>> {
+        if !self.symlink_metadata(path)?.is_dir() {
+            return Err(io::Error::other("not a directory"));
+        }
+        Ok(self
+            .entries
+            .borrow()
+            .iter()
+            .filter(|(it, _)| it.parent() == Some(path))
+            .map(|(it, (meta, _, _))| (it.clone(), meta.clone()))
+            .collect())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let mut r = PathBuf::new();
+        // what is left to resolve, the next last
+        let mut rest: Vec<_> = path.iter().rev().map(|it| it.to_os_string()).collect();
+        // (as many links as Linux follows before giving up)
+        let mut hops = 40;
+        while let Some(comp) = rest.pop() {
+            if comp == ".." {
+                r.pop();
+                continue;
+            } else if comp == "." {
+                continue;
+            }
+            r.push(comp);
+            if self.symlink_metadata(&r)?.is_symlink() {
+                if 0 == hops {
+                    return Err(io::Error::other("too many levels of links"));
+                }
+                hops -= 1;
+                let target = self.read_link(&r)?;
+                r.pop();
+                rest.extend(target.iter().rev().map(|it| it.to_os_string()));
+            }
+        }
+        Ok(r)
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let path = self.canonicalize(path)?;
+        match self.entries.borrow().get(&path) {
+            Some((meta, _, content)) if meta.is_file() => Ok(content
+                .iter()
+                .skip(offset as usize)
+                .take(len as usize)
+                .copied()
+                .collect()),
+            Some(_) => Err(io::Error::other("not a regular file")),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory() {
+        let mem = Memory::default();
+        mem.file("/r/a/b", 3);
+        mem.link("/r/c", "/r/a/b");
+        assert!(mem.metadata(Path::new("/r/a")).unwrap().is_dir());
+        assert_eq!(mem.metadata(Path::new("/r/c")).unwrap().len, 3);
+        assert!(mem.symlink_metadata(Path::new("/r/c")).unwrap().is_symlink());
+        let names = |path| {
+            mem.read_dir(Path::new(path))
+                .unwrap()
+                .into_iter()
+                .map(|(it, _)| it)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("/r"), ["/r/a", "/r/c"].map(PathBuf::from));
+        mem.remove("/r/a");
+        assert_eq!(names("/r"), [PathBuf::from("/r/c")]);
+        assert!(mem.metadata(Path::new("/r/c")).is_err());
+    }
+
+    #[test]
+    fn test_memory_links() {
+        let mem = Memory::default();
+        mem.text("/r/a/b", "hello");
+        mem.link("/r/c", "a");
+        mem.link("/r/a/d", "../c/b");
+        mem.link("/r/e", "/r/e");
+        let canonical = |path| mem.canonicalize(Path::new(path));
+        assert_eq!(canonical("/r/c/b").unwrap(), PathBuf::from("/r/a/b"));
+        assert_eq!(canonical("/r/a/d").unwrap(), PathBuf::from("/r/a/b"));
+        assert!(canonical("/r/e").is_err());
+        assert_eq!(mem.read_at(Path::new("/r/a/d"), 1, 3).unwrap(), b"ell");
+        assert!(mem.read_at(Path::new("/r/c"), 0, 3).is_err());
+    }
+}
@@ -5,7 +5,7 @@ pub struct TextBlock {
     style: Style,
 }
 
-impl<'me> Widget for &'me TextBlock {
+impl Widget for &TextBlock {
     fn render(self, area: Rect, buf: &mut Buffer) {
         for (k, vline) in self.text.iter().take(area.height as usize).enumerate() {
             buf.set_string(area.x, area.y + k as u16, vline, self.style);
@@ -39,7 +39,7 @@ impl TextBlock {
                 vlines.push(
                     " ".repeat(indent)
                         .chars()
-                        .chain(['\u{21aa}', ' '].into_iter())
+                        .chain(['\u{21aa}', ' '])
                         .chain(chs.by_ref().take(width - indent - 2))
                         .collect::<String>(),
                 );
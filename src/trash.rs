@@ -0,0 +1,242 @@
+// the home trash, following the freedesktop.org specification:
+// entries are moved into `$XDG_DATA_HOME/Trash/files` and described
+// by a `.trashinfo` file in `$XDG_DATA_HOME/Trash/info`
+use crate::{
+    fileop,
+    node::{time_to_string, TimeFormat},
+};
+use dirs::data_dir;
+use std::{
+    borrow::Cow,
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+pub struct Entry {
+    /// name within the trash
+    pub name: String,
+    /// where it was deleted from
+    pub path: PathBuf,
+    pub date: String,
+}
+
+fn trash_dir() -> io::Result<PathBuf> {
+    data_dir()
+        .map(|it| it.join("Trash"))
+        .ok_or_else(|| io::Error::other("could not find the user data directory"))
+}
+
+/// now, in local time as the spec wants (`YYYY-MM-DDThh:mm:ss`)
+#[cfg(unix)]
+fn deletion_date() -> String {
+    // SAFETY: a null pointer is allowed, the time is only returned
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    // SAFETY: `tm` is plain old data, all zeroes is a valid value
+    let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+    // SAFETY: both pointers are to locals that outlive the call
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return utc_date();
+    }
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+#[cfg(not(unix))]
+fn deletion_date() -> String {
+    utc_date()
+}
+
+fn utc_date() -> String {
+    time_to_string(SystemTime::now(), TimeFormat::Iso)
+        .trim_end_matches('Z')
+        .to_string()
+}
+
+// paths are encoded from (and decoded to) their raw bytes, a name
+// that is not valid UTF-8 still comes back the same
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Owned(path.to_string_lossy().as_bytes().to_vec())
+}
+
+#[cfg(unix)]
+fn bytes_path(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes).into()
+}
+
+#[cfg(not(unix))]
+fn bytes_path(bytes: Vec<u8>) -> PathBuf {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
+
+fn encode(path: &Path) -> String {
+    path_bytes(path)
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> PathBuf {
+    let bytes = text.as_bytes();
+    let mut r = Vec::with_capacity(bytes.len());
+    let mut k = 0;
+    while k < bytes.len() {
+        let hex = bytes
+            .get(k + 1..k + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[k], hex) {
+            (b'%', Some(b)) => {
+                r.push(b);
+                k += 3;
+            }
+            (b, _) => {
+                r.push(b);
+                k += 1;
+            }
+        }
+    }
+    bytes_path(r)
+}
+
+/// move `path` (which should be absolute) to the trash
+/// @ret the name it was given within the trash
+pub fn put(path: &Path) -> io::Result<String> {
+    let trash = trash_dir()?;
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let Some(name) = path.file_name() else {
+        return Err(io::Error::other("cannot trash the root of the file system"));
+    };
+    let name = name.to_string_lossy();
+
+    // reserve a name by creating its info file first
+    let (mut file, name) = (1..)
+        .map(|n| {
+            if 1 == n {
+                name.to_string()
+            } else {
+                format!("{name}.{n}")
+            }
+        })
+        .find_map(|name| {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(info.join(format!("{name}.trashinfo")))
+                .ok()
+                .map(|file| (file, name))
+        })
+        .unwrap();
+
+    let res = writeln!(
+        file,
+        "[Trash Info]\nPath={}\nDeletionDate={}",
+        encode(path),
+        deletion_date()
+    )
+    .and_then(|_| fileop::relocate(path, &files.join(&name)));
+
+    match res {
+        Ok(()) => Ok(name),
+        Err(err) => {
+            let _ = fs::remove_file(info.join(format!("{name}.trashinfo")));
+            Err(err)
+        }
+    }
+}
+
+/// every entries in the trash, most recently deleted first
+pub fn list() -> io::Result<Vec<Entry>> {
+    let info = trash_dir()?.join("info");
+    if !info.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut r = info
+        .read_dir()?
+        .filter_map(Result::ok)
+        .filter_map(|ent| {
+            let file_name = ent.file_name().to_string_lossy().to_string();
+            let name = file_name.strip_suffix(".trashinfo")?.to_string();
+            let text = fs::read_to_string(ent.path()).ok()?;
+            let mut path = None;
+            let mut date = String::new();
+            for line in text.lines() {
+                if let Some(it) = line.strip_prefix("Path=") {
+                    path = Some(decode(it));
+                } else if let Some(it) = line.strip_prefix("DeletionDate=") {
+                    date = it.to_string();
+                }
+            }
+            Some(Entry {
+                name,
+                path: path?,
+                date,
+            })
+        })
+        .collect::<Vec<_>>();
+    r.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(r)
+}
+
+/// move an entry back where it was deleted from
+pub fn restore(entry: &Entry) -> io::Result<()> {
+    let trash = trash_dir()?;
+    if fs::symlink_metadata(&entry.path).is_ok() {
+        return Err(io::Error::other(format!(
+            "'{}' already exists",
+            entry.path.to_string_lossy()
+        )));
+    }
+    if let Some(parent) = entry.path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fileop::relocate(&trash.join("files").join(&entry.name), &entry.path)?;
+    fs::remove_file(trash.join("info").join(format!("{}.trashinfo", entry.name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+    use std::path::Path;
+
+    #[test]
+    fn test_encode_decode() {
+        let path = Path::new("/some dir/ça%va.txt");
+        assert_eq!(encode(path), "/some%20dir/%C3%A7a%25va.txt");
+        assert_eq!(decode(&encode(path)), path);
+
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+            let path = Path::new(OsStr::from_bytes(b"/not/utf\xff8"));
+            assert_eq!(encode(path), "/not/utf%FF8");
+            assert_eq!(decode(&encode(path)), path);
+        }
+    }
+}
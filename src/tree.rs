@@ -1,5 +1,5 @@
 use crate::{
-    node::Node,
+    node::{Node, Renewed},
     view::{Offset, State, View},
 };
 use serde::{Deserialize, Serialize};
@@ -35,16 +35,16 @@ const INDENT_WIDTH: u16 = 4;
 fn render_name(
     tree_node: &Node,
     state_node: &State,
+    file_name: &str,
     buf: &mut Buffer,
     (indent, line): (u16, u16),
     area: Rect,
-    is_cursor: bool,
+    (is_cursor, is_visual): (bool, bool),
 ) -> usize {
     if area.width <= indent {
         return 0;
     }
 
-    let file_name = tree_node.file_name();
     let sty = {
         let style = if state_node.marked {
             Style::default()
@@ -54,6 +54,11 @@ fn render_name(
         } else {
             tree_node.style()
         };
+        let style = if is_visual {
+            style.bg(Color::DarkGray)
+        } else {
+            style
+        };
         if is_cursor {
             style.add_modifier(Modifier::REVERSED)
         } else {
@@ -101,6 +106,23 @@ fn render_name(
     }
 }
 
+struct Visual<'a> {
+    path: Vec<usize>,
+    range: &'a [Vec<usize>],
+}
+
+impl Visual<'_> {
+    fn contains(&self, rest: &[usize]) -> bool {
+        !self.range.is_empty()
+            && self.range.iter().any(|it| {
+                it.len() == self.path.len() + rest.len()
+                    && it.starts_with(&self.path)
+                    && it.ends_with(rest)
+            })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn render_r(
     state_node: &State,
     tree_node: &Node,
@@ -109,6 +131,7 @@ fn render_r(
     bump: i32,
     area: Rect,
     cursor_path: Option<&[usize]>,
+    visual: &mut Visual,
 ) {
     // this node
     let mut name_width = 0;
@@ -116,18 +139,36 @@ fn render_r(
         name_width = render_name(
             tree_node,
             state_node,
+            tree_node.file_name(),
             buf,
             ((curr.shift + bump) as u16, curr.scroll as u16),
             area,
-            if let Some(v) = cursor_path {
-                v.is_empty()
-            } else {
-                false
-            },
+            (
+                if let Some(v) = cursor_path {
+                    v.is_empty()
+                } else {
+                    false
+                },
+                visual.contains(&[]),
+            ),
         ) as i32;
     }
     curr.scroll += 1;
 
+    if let (true, Some(flat)) = (state_node.unfolded, &state_node.flat) {
+        render_flat(
+            state_node,
+            tree_node,
+            flat,
+            buf,
+            curr,
+            area,
+            cursor_path,
+            visual,
+        );
+        return;
+    }
+
     // recurse
     if state_node.unfolded && !state_node.children.is_empty() {
         let count = state_node.children.len();
@@ -155,6 +196,7 @@ fn render_r(
 
             let p_indent = curr.shift;
             let p_line = curr.scroll;
+            visual.path.push(in_state_idx);
             render_r(
                 state_node,
                 tree_node,
@@ -172,7 +214,9 @@ fn render_r(
                     }
                     None
                 }),
+                visual,
             );
+            visual.path.pop();
 
             if INDENT_WIDTH as i32 <= p_indent && 0 <= p_line {
                 buf.set_string(
@@ -204,6 +248,119 @@ fn render_r(
     };
 }
 
+#[allow(clippy::too_many_arguments)]
+fn render_flat(
+    state_node: &State,
+    tree_node: &Node,
+    flat: &[Vec<usize>],
+    buf: &mut Buffer,
+    curr: &mut Offset,
+    area: Rect,
+    cursor_path: Option<&[usize]>,
+    visual: &Visual,
+) {
+    curr.shift += INDENT_WIDTH as i32;
+    let count = flat.len();
+
+    for (k, path) in flat.iter().enumerate() {
+        if area.height as i32 <= curr.scroll {
+            break;
+        }
+
+        if 0 <= curr.shift && 0 <= curr.scroll {
+            let (st, nd, rel) = state_node.flat_entry(tree_node, path);
+            render_name(
+                nd,
+                st,
+                &rel,
+                buf,
+                (curr.shift as u16, curr.scroll as u16),
+                area,
+                (cursor_path == Some(path), visual.contains(path)),
+            );
+            if INDENT_WIDTH as i32 <= curr.shift {
+                buf.set_string(
+                    area.x + (curr.shift as u16) - INDENT_WIDTH,
+                    area.y + (curr.scroll as u16),
+                    if k == count - 1 { BRANCH_LAST } else { BRANCH },
+                    Style::default(),
+                );
+            }
+        }
+        curr.scroll += 1;
+    }
+
+    curr.shift -= INDENT_WIDTH as i32;
+}
+
+/// `INDENT`, `BRANCH` and `BRANCH_LAST` as printed, each with the
+/// column up to the name
+fn print_glyphs(ascii: bool) -> [String; 3] {
+    if ascii {
+        ["|   ", "|-- ", "`-- "].map(String::from)
+    } else {
+        [INDENT, BRANCH, BRANCH_LAST].map(|it| format!("{} ", it.replace('\u{a0}', " ")))
+    }
+}
+
+/// same layout as `render_r` but as lines of text; `head` is what
+/// comes before the name on its line, `indent` before the lines below
+fn print_r(
+    state_node: &State,
+    tree_node: &Node,
+    head: String,
+    indent: &str,
+    glyphs: &[String; 3],
+    lines: &mut Vec<String>,
+) {
+    let mut line = head + tree_node.file_name() + &tree_node.decoration();
+    if state_node.unfolded && state_node.children.is_empty() {
+        line += " (/)";
+    }
+
+    if let (true, Some(flat)) = (state_node.unfolded, &state_node.flat) {
+        lines.push(line);
+        for (k, path) in flat.iter().enumerate() {
+            let (_, nd, rel) = state_node.flat_entry(tree_node, path);
+            let glyph = &glyphs[if k == flat.len() - 1 { 2 } else { 1 }];
+            lines.push(format!("{indent}{glyph}{rel}{}", nd.decoration()));
+        }
+        return;
+    }
+
+    if !state_node.unfolded || state_node.children.is_empty() {
+        lines.push(line);
+        return;
+    }
+
+    let chs = tree_node.loaded_children().unwrap();
+    let count = state_node.children.len();
+    // an only child is on the same line
+    if 1 == count {
+        let (in_node_idx, state_node) = &state_node.children[0];
+        print_r(state_node, &chs[*in_node_idx], line, indent, glyphs, lines);
+        return;
+    }
+
+    lines.push(line);
+    for (in_state_idx, (in_node_idx, state_node)) in state_node.children.iter().enumerate() {
+        let is_last = in_state_idx == count - 1;
+        let (head, below) = if is_last {
+            (&glyphs[2], "    ")
+        } else {
+            (&glyphs[1], glyphs[0].as_str())
+        };
+        print_r(
+            state_node,
+            &chs[*in_node_idx],
+            format!("{indent}{head}"),
+            &format!("{indent}{below}"),
+            glyphs,
+            lines,
+        );
+    }
+}
+
 impl StatefulWidget for &Tree {
     type State = View;
 
@@ -218,6 +375,7 @@ impl StatefulWidget for &Tree {
             scroll: -view_offset.scroll,
         };
 
+        let range = state.visual_range();
         render_r(
             &state.root,
             &self.root,
@@ -226,6 +384,10 @@ impl StatefulWidget for &Tree {
             0,
             area,
             Some(state.cursor_path()),
+            &mut Visual {
+                path: Vec::new(),
+                range: &range,
+            },
         );
     }
 }
@@ -237,6 +399,27 @@ impl Tree {
         })
     }
 
+    /// what is visible in `view`, as text (eg. for `--print`)
+    pub fn print(&self, view: &View, ascii: bool) -> String {
+        let mut lines = Vec::new();
+        print_r(
+            &view.root,
+            &self.root,
+            String::new(),
+            "",
+            &print_glyphs(ascii),
+            &mut lines,
+        );
+        lines.into_iter().map(|it| it + "\n").collect()
+    }
+
+    /// see `Node::from_paths`
+    pub fn from_paths(root: PathBuf, paths: Vec<PathBuf>) -> Tree {
+        Tree {
+            root: Node::from_paths(root, paths),
+        }
+    }
+
     /// re-create the tree from the file system; even
     /// though a Tree is lazy, this is NOT a no-op: it
     /// tries to re-load the nodes that previously where
@@ -246,4 +429,130 @@ impl Tree {
             root: self.root.renew()?,
         })
     }
+
+    /// same as `renew` but limited to the given paths, in place
+    pub fn renew_within(&mut self, paths: &[PathBuf]) -> io::Result<Vec<Renewed>> {
+        self.root.renew_within(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{Memory, TreeSource};
+    use std::{path::Path, rc::Rc};
+
+    #[test]
+    fn test_print() {
+        let mem = Rc::new(Memory::default());
+        mem.file("/r/a/b/x", 0);
+        mem.file("/r/c/y", 0);
+        mem.file("/r/c/z", 0);
+        mem.dir("/r/d");
+        mem.file("/r/e", 0);
+        let source: Rc<dyn TreeSource> = mem;
+        let mut tree = Tree {
+            root: Node::new_root_in(&source, PathBuf::from("/r")).unwrap(),
+        };
+        let mut view = View::new(&tree.root).unwrap();
+        assert!(view.unfold_rec(&mut tree, usize::MAX).is_empty());
+        assert_eq!(
+            tree.print(&view, true),
+            "r/\n|-- a/b/x\n|-- c/\n|   |-- y\n|   `-- z\n|-- d/ (/)\n`-- e\n"
+        );
+        assert!(tree.print(&view, false).starts_with("r/\n\u{251c}\u{2500}\u{2500} a/b/x\n"));
+    }
+
+    #[test]
+    fn test_link_loop() {
+        let mem = Rc::new(Memory::default());
+        mem.file("/r/a/x", 0);
+        mem.link("/r/a/up", "..");
+        let source: Rc<dyn TreeSource> = mem;
+        let mut tree = Tree {
+            root: Node::new_root_in(&source, PathBuf::from("/r")).unwrap(),
+        };
+        let mut view = View::new(&tree.root).unwrap();
+        // (the link back up is not followed, whatever is on disk)
+        assert!(view.unfold_rec(&mut tree, usize::MAX).is_empty());
+        assert_eq!(tree.print(&view, true), "r/a/\n|-- up@ ~> ..\n`-- x\n");
+    }
+
+    #[test]
+    fn test_renew_within() {
+        let mem = Rc::new(Memory::default());
+        mem.file("/r/a/x", 0);
+        mem.file("/r/c/y", 0);
+        mem.file("/r/e", 0);
+        let source: Rc<dyn TreeSource> = mem.clone();
+        let mut tree = Tree {
+            root: Node::new_root_in(&source, PathBuf::from("/r")).unwrap(),
+        };
+        let mut view = View::new(&tree.root).unwrap();
+        assert!(view.unfold_rec(&mut tree, usize::MAX).is_empty());
+        assert!(view.reveal(&mut tree, Path::new("e")).unwrap());
+
+        mem.file("/r/b", 0);
+        mem.remove("/r/a");
+        mem.file("/r/c/w", 0);
+        let paths = ["/r/b", "/r/a", "/r/c/w"].map(PathBuf::from);
+        let renewed = tree.renew_within(&paths).unwrap();
+        view.fixup_within(&tree, &renewed);
+        assert_eq!(
+            tree.print(&view, true),
+            "r/\n|-- b\n|-- c/\n|   |-- w\n|   `-- y\n`-- e\n"
+        );
+        assert_eq!(view.at_cursor_pair(&tree).1.file_name(), "e");
+    }
+
+    #[test]
+    fn test_view_history() {
+        let mem = Rc::new(Memory::default());
+        mem.file("/r/a/x", 0);
+        mem.file("/r/c/y", 0);
+        mem.file("/r/e", 0);
+        let source: Rc<dyn TreeSource> = mem.clone();
+        let mut tree = Tree {
+            root: Node::new_root_in(&source, PathBuf::from("/r")).unwrap(),
+        };
+        let mut view = View::new(&tree.root).unwrap();
+        view.unfold_root(&mut tree).unwrap();
+        let shallow = tree.print(&view, true);
+
+        view.history_checkpoint(&tree);
+        assert!(view.reveal(&mut tree, Path::new("c/y")).unwrap());
+        view.history_commit(&tree);
+        let revealed = tree.print(&view, true);
+        view.history_checkpoint(&tree);
+        view.at_cursor_mut().marked = true;
+        view.cursor_to_root();
+        assert!(view.unfold_rec(&mut tree, usize::MAX).is_empty());
+        view.history_commit(&tree);
+        let unfolded = tree.print(&view, true);
+        // cursor moves are not recorded
+        view.history_checkpoint(&tree);
+        view.cursor_to_root();
+        view.history_commit(&tree);
+
+        // (changes are kept by names, so they follow the tree)
+        mem.file("/r/b", 0);
+        let renewed = tree.renew_within(&[PathBuf::from("/r/b")]).unwrap();
+        view.fixup_within(&tree, &renewed);
+        let unfolded = unfolded.replace("|-- c/", "|-- b\n|-- c/");
+
+        assert!(view.undo(&mut tree));
+        assert_eq!(tree.print(&view, true), revealed.replace("|-- c/", "|-- b\n|-- c/"));
+        assert_eq!(view.at_cursor_pair(&tree).1.file_name(), "y");
+        assert!(!view.at_cursor().marked);
+        assert!(view.undo(&mut tree));
+        assert_eq!(tree.print(&view, true), shallow.replace("|-- c/", "|-- b\n|-- c/"));
+        assert!(!view.undo(&mut tree));
+
+        assert!(view.redo(&mut tree));
+        assert!(view.redo(&mut tree));
+        assert!(!view.redo(&mut tree));
+        assert_eq!(tree.print(&view, true), unfolded);
+        assert!(view.reveal(&mut tree, Path::new("c/y")).unwrap());
+        assert!(view.at_cursor().marked);
+    }
 }
@@ -1,11 +1,46 @@
 use crate::{
-    node::{Filtering, Movement, Node, Sorting, SortingProp},
+    node::{Filtering, Movement, Node, Renewed, Sorting, SortingProp},
     tree::Tree,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, io};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+    path::{Component, Path, PathBuf},
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// used to not loop through links; what only exists in the tree
+/// (eg. within archives or documents) is its own identity
+fn identity(node: &Node) -> PathBuf {
+    node.source()
+        .canonicalize(node.as_path())
+        .unwrap_or_else(|_| node.as_path().to_path_buf())
+}
+
+/// names along `path` (relative to the root), `..` resolved lexically
+/// (ie. `a/../b` is `b`); `None` if a name cannot be in the tree (not
+/// UTF-8), an error if it goes above the root
+fn path_names(path: &Path) -> io::Result<Option<Vec<&str>>> {
+    let mut names = Vec::new();
+    for c in path.components() {
+        match c {
+            Component::Normal(name) => {
+                let Some(name) = name.to_str() else { return Ok(None); };
+                names.push(name);
+            }
+            Component::ParentDir if names.pop().is_none() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "path goes above the root",
+                ));
+            }
+            _ => (),
+        }
+    }
+    Ok(Some(names))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct ViewSettings {
     sort: Sorting,
     filters: Vec<Filtering>,
@@ -54,11 +89,15 @@ impl ViewSettings {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct State {
     pub unfolded: bool,
     pub marked: bool,
     pub children: Vec<(usize, State)>,
+    /// when flattened, the paths (of in-state indices) to every
+    /// non-directory node below, in display order
+    #[serde(default)]
+    pub flat: Option<Vec<Vec<usize>>>,
 }
 
 impl State {
@@ -69,6 +108,7 @@ impl State {
             children: node
                 .loaded_children()
                 .map_or(Ok(Vec::new()), |ok| settings.make_node_state_mapping(ok))?,
+            flat: None,
         })
     }
 
@@ -80,15 +120,16 @@ impl State {
         inout_path: &mut Vec<usize>, // path into 'self' related to 'pnode'; to update for new related to 'node'
         path_cur: usize,
     ) -> State {
-        State {
-            unfolded: self.unfolded,
+        // (it may not be loaded anymore, eg. it could not be read again)
+        let loaded = match (self.unfolded, pnode.loaded_children(), node.loaded_children()) {
+            (true, Some(plo_chs), Some(lo_chs)) => Some((plo_chs, lo_chs)),
+            _ => None,
+        };
+        let mut r = State {
+            unfolded: loaded.is_some(),
             marked: self.marked,
-            children: if !self.unfolded {
-                inout_path.truncate(path_cur);
-                Vec::new()
-            } else {
-                let Some(plo_chs) = pnode.loaded_children() else { unreachable!() };
-                let Some(lo_chs) = node.loaded_children() else { unreachable!() };
+            flat: None,
+            children: if let Some((plo_chs, lo_chs)) = loaded {
 
                 let mut done = HashSet::<&str>::new();
                 let mut found_onpath = false;
@@ -148,12 +189,139 @@ impl State {
                 }
 
                 children
+            } else {
+                inout_path.truncate(path_cur);
+                Vec::new()
             }, // children: ..
-        } // State { .. }
+        }; // State { .. }
+        // (a folded one is listed again when unfolded, see `unfold`)
+        if self.flat.is_some() {
+            r.flat = Some(if r.unfolded { r.flat_paths(node, settings) } else { Vec::new() });
+        }
+        r
+    }
+
+    /// see `View::fixup_within`; `names` lead from `self` (`node`
+    /// is already the renewed one) to what changed
+    fn follow_renewed(
+        &mut self,
+        node: &Node,
+        names: &[&str],
+        renewed: &Renewed,
+        settings: &ViewSettings,
+        inout_path: &mut Vec<usize>, // same as for `renew`
+        path_cur: usize,
+    ) {
+        let Some((first, rest)) = names.split_first() else {
+            match renewed {
+                Renewed::Node(old) => *self = self.renew(old, node, settings, inout_path, path_cur),
+                Renewed::Listing(_, old_names) => {
+                    self.relisted(node, old_names, settings, inout_path, path_cur)
+                }
+            }
+            return;
+        };
+        let Some(chs) = node.loaded_children() else { return; };
+        let Some(idx) = self
+            .children
+            .iter()
+            .position(|(k, _)| chs.get(*k).is_some_and(|ch| ch.file_name() == *first))
+        else {
+            return;
+        };
+        let (k, st) = &mut self.children[idx];
+        if path_cur < inout_path.len() && idx == inout_path[path_cur] {
+            st.follow_renewed(&chs[*k], rest, renewed, settings, inout_path, path_cur + 1);
+        } else {
+            st.follow_renewed(&chs[*k], rest, renewed, settings, &mut Vec::new(), path_cur + 1);
+        }
+    }
+
+    // the children of `node` were listed again, find the states back
+    // by name (same as `renew` but only at this level)
+    fn relisted(
+        &mut self,
+        node: &Node,
+        old_names: &[String],
+        settings: &ViewSettings,
+        inout_path: &mut Vec<usize>,
+        path_cur: usize,
+    ) {
+        if self.children.is_empty() && !self.unfolded {
+            return;
+        }
+        let Some(lo_chs) = node.loaded_children() else {
+            self.children.clear();
+            inout_path.truncate(path_cur);
+            return;
+        };
+        let on_path = inout_path
+            .get(path_cur)
+            .and_then(|idx| self.children.get(*idx))
+            .and_then(|(k, _)| old_names.get(*k))
+            .cloned();
+
+        let mut done = HashSet::<&str>::new();
+        let mut children = Vec::with_capacity(lo_chs.len());
+        for (k, st) in std::mem::take(&mut self.children) {
+            let Some(name) = old_names.get(k) else { continue; };
+            if let Some(nk) = lo_chs.iter().position(|ch| ch.file_name() == name) {
+                done.insert(lo_chs[nk].file_name());
+                children.push((nk, st));
+            }
+        }
+        children.extend(
+            lo_chs
+                .iter()
+                .enumerate()
+                .filter(|(_, ch)| !done.contains(ch.file_name()))
+                .filter_map(|(k, ch)| State::new(ch, settings).map(|st| (k, st)).ok()),
+        );
+        self.children = settings.correct_node_state_mapping(lo_chs, children);
+
+        let found = on_path.and_then(|name| {
+            self.children
+                .iter()
+                .position(|(k, _)| lo_chs[*k].file_name() == name)
+        });
+        match found {
+            Some(idx) => inout_path[path_cur] = idx,
+            None => inout_path.truncate(path_cur),
+        }
+    }
+
+    fn flags(&self) -> Flags {
+        Flags {
+            unfolded: self.unfolded,
+            marked: self.marked,
+            flat: self.flat.is_some(),
+        }
+    }
+
+    // the flags of every state below (itself included) that has any
+    // set, by path (names joined with '/', relative to `self`)
+    fn collect_flags(&self, node: &Node, path: &mut String, out: &mut Vec<(String, Flags)>) {
+        let flags = self.flags();
+        if flags != Flags::default() {
+            out.push((path.clone(), flags));
+        }
+        let Some(chs) = node.loaded_children() else { return; };
+        for (k, st) in &self.children {
+            let ch = &chs[*k];
+            let len = path.len();
+            if 0 != len {
+                path.push('/');
+            }
+            path.push_str(ch.file_name());
+            st.collect_flags(ch, path, out);
+            path.truncate(len);
+        }
     }
 
     pub fn visible_height(&self) -> usize {
-        if self.unfolded {
+        if let (true, Some(flat)) = (self.unfolded, &self.flat) {
+            1 + flat.len()
+        } else if self.unfolded {
             (if 1 == self.children.len() { 0 } else { 1 })
                 + self
                     .children
@@ -172,13 +340,208 @@ impl State {
                 .and_then(|v| settings.make_node_state_mapping(v))?;
         }
         self.unfolded = true;
+        // a flat listing needs every sub-directory unfolded
+        if self.flat.is_some() {
+            let mut ancestors = vec![identity(node)];
+            // (what cannot be read is only missing from the listing)
+            self.unfold_children_rec(node, settings, usize::MAX, &mut ancestors, &mut Vec::new());
+            self.flat = Some(self.flat_paths(node, settings));
+        }
         Ok(())
     }
 
+    /// see `View::refresh_flat`
+    fn refresh_flat(&mut self, node: &mut Node, settings: &ViewSettings) {
+        if !self.unfolded {
+            return;
+        }
+        if self.flat.is_some() {
+            let _ = self.unfold(node, settings);
+            return;
+        }
+        let Some(chs) = node.loaded_children_mut() else { return; };
+        for (k, st) in &mut self.children {
+            st.refresh_flat(&mut chs[*k], settings);
+        }
+    }
+
     pub fn fold(&mut self) {
         self.unfolded = false;
     }
 
+    fn collect_flat<'a>(
+        &'a self,
+        node: &'a Node,
+        path: &mut Vec<usize>,
+        names: &mut Vec<&'a str>,
+        out: &mut Vec<(Vec<usize>, String, &'a Node)>,
+    ) {
+        let Some(chs) = node.loaded_children() else { return; };
+        for (idx, (k, st)) in self.children.iter().enumerate() {
+            let ch = &chs[*k];
+            path.push(idx);
+            names.push(ch.file_name());
+            if st.unfolded && ch.loaded_children().is_some() {
+                st.collect_flat(ch, path, names, out);
+            } else if !ch.is_dir() {
+                out.push((path.clone(), names.join("/"), ch));
+            }
+            names.pop();
+            path.pop();
+        }
+    }
+
+    /// mark children matching `predicate`, recursing up to `depth`
+    /// levels; a directory is left unfolded only if something was
+    /// marked within it, @ret the number of nodes marked
+    fn mark_matching(
+        &mut self,
+        node: &mut Node,
+        settings: &ViewSettings,
+        depth: usize,
+        predicate: &impl Fn(&Node) -> bool,
+        ancestors: &mut Vec<PathBuf>,
+        errors: &mut Vec<String>,
+    ) -> usize {
+        let was_unfolded = self.unfolded;
+        if 0 == depth {
+            return 0;
+        }
+        if let Err(err) = self.unfold(node, settings) {
+            errors.push(format!("{}: {err}", node.as_path().to_string_lossy()));
+            return 0;
+        }
+        let Some(chs) = node.loaded_children_mut() else { return 0; };
+        let mut count = 0;
+        for (k, st) in &mut self.children {
+            let ch = &mut chs[*k];
+            if predicate(ch) {
+                st.marked = true;
+                count += 1;
+            }
+            if ch.is_file() || depth < 2 {
+                continue;
+            }
+            let canonical = identity(ch);
+            if ancestors.contains(&canonical) {
+                continue;
+            }
+            ancestors.push(canonical);
+            count += st.mark_matching(ch, settings, depth - 1, predicate, ancestors, errors);
+            ancestors.pop();
+        }
+        if 0 == count {
+            self.unfolded = was_unfolded;
+        }
+        count
+    }
+
+    /// see `State::flat`; sorting applies across the whole subtree
+    fn flat_paths(&self, node: &Node, settings: &ViewSettings) -> Vec<Vec<usize>> {
+        let mut r = Vec::new();
+        self.collect_flat(node, &mut Vec::new(), &mut Vec::new(), &mut r);
+        match settings.sort.prop {
+            SortingProp::None => (),
+            SortingProp::Name => r.sort_by(|(_, l, _), (_, r, _)| l.cmp(r)),
+            prop => r.sort_by(|(_, lrel, l), (_, rrel, r)| {
+                l.cmp_by(r, Sorting::new(prop, false))
+                    .then_with(|| lrel.cmp(rrel))
+            }),
+        }
+        if settings.reverse {
+            r.reverse();
+        }
+        r.into_iter().map(|(path, _, _)| path).collect()
+    }
+
+    /// state, node and path (joined names) relative to `self`/`node`
+    pub fn flat_entry<'a>(&'a self, node: &'a Node, path: &[usize]) -> (&'a State, &'a Node, String) {
+        let (st, nd, names) = path
+            .iter()
+            .fold((self, node, Vec::new()), |(st, nd, mut names), idx| {
+                let (k, st) = &st.children[*idx];
+                let nd = &nd.loaded_children().unwrap()[*k];
+                names.push(nd.file_name());
+                (st, nd, names)
+            });
+        (st, nd, names.join("/"))
+    }
+
+    pub fn fold_rec(&mut self) {
+        self.unfolded = false;
+        for (_, ch) in &mut self.children {
+            ch.fold_rec();
+        }
+    }
+
+    // `ancestors` are the canonical paths of the directories above,
+    // used to avoid looping through links; what could not be read
+    // is added to `errors`
+    fn unfold_rec(
+        &mut self,
+        node: &mut Node,
+        settings: &ViewSettings,
+        depth: usize,
+        ancestors: &mut Vec<PathBuf>,
+        errors: &mut Vec<String>,
+    ) {
+        if 0 == depth {
+            return;
+        }
+        if let Err(err) = self.unfold(node, settings) {
+            errors.push(format!("{}: {err}", node.as_path().to_string_lossy()));
+            return;
+        }
+        self.unfold_children_rec(node, settings, depth - 1, ancestors, errors);
+    }
+
+    fn unfold_children_rec(
+        &mut self,
+        node: &mut Node,
+        settings: &ViewSettings,
+        depth: usize,
+        ancestors: &mut Vec<PathBuf>,
+        errors: &mut Vec<String>,
+    ) {
+        let Some(chs) = node.loaded_children_mut() else { return; };
+        for (k, st) in &mut self.children {
+            let ch = &mut chs[*k];
+            if ch.is_file() {
+                continue;
+            }
+            let canonical = identity(ch);
+            if ancestors.contains(&canonical) {
+                continue;
+            }
+            ancestors.push(canonical);
+            st.unfold_rec(ch, settings, depth, ancestors, errors);
+            ancestors.pop();
+        }
+    }
+
+    fn reveal(
+        &mut self,
+        node: &mut Node,
+        settings: &ViewSettings,
+        names: &[&str],
+        out_path: &mut Vec<usize>,
+    ) -> io::Result<bool> {
+        let Some((first, rest)) = names.split_first() else { return Ok(true); };
+        self.unfold(node, settings)?;
+        let chs = node.loaded_children_mut().unwrap();
+        let Some(idx) = self
+            .children
+            .iter()
+            .position(|(k, _)| chs[*k].file_name() == *first)
+        else {
+            // either does not exist or is filtered out
+            return Ok(false);
+        };
+        out_path.push(idx);
+        let (k, st) = &mut self.children[idx];
+        st.reveal(&mut chs[*k], settings, rest, out_path)
+    }
+
     // special case: movement::backward with skip 0 means starts at -1
     pub fn scan_to(
         &mut self,
@@ -247,6 +610,60 @@ impl State {
             }
         }
     }
+
+    // same as above but paths made from the names (so relative and
+    // not going through links' targets)
+    fn collect_marked_paths(&self, node: &Node, path: &mut PathBuf, out_res: &mut Vec<PathBuf>) {
+        if self.marked {
+            out_res.push(path.clone());
+        }
+        if let Some(chs) = node.loaded_children() {
+            for (k, st) in &self.children {
+                let ch = &chs[*k];
+                path.push(ch.file_name());
+                st.collect_marked_paths(ch, path, out_res);
+                path.pop();
+            }
+        }
+    }
+
+    fn set_marked_rec(&mut self, marked: bool) {
+        self.marked = marked;
+        for (_, st) in &mut self.children {
+            st.set_marked_rec(marked);
+        }
+    }
+
+    fn invert_marked(&mut self, recursive: bool) {
+        for (_, st) in &mut self.children {
+            st.marked = !st.marked;
+            if recursive {
+                st.invert_marked(true);
+            }
+        }
+    }
+
+    // the state and node at `names`; loads what is needed
+    // to reach it, but does not unfold
+    fn reach<'a>(
+        &'a mut self,
+        node: &'a mut Node,
+        settings: &ViewSettings,
+        names: &[&str],
+    ) -> Option<(&'a mut State, &'a mut Node)> {
+        let Some((first, rest)) = names.split_first() else { return Some((self, node)); };
+        if self.children.is_empty() {
+            let was_unfolded = self.unfolded;
+            self.unfold(node, settings).ok()?;
+            self.unfolded = was_unfolded;
+        }
+        let chs = node.loaded_children_mut()?;
+        let (k, st) = self
+            .children
+            .iter_mut()
+            .find(|(k, _)| chs[*k].file_name() == *first)?;
+        st.reach(&mut chs[*k], settings, rest)
+    }
 }
 
 /// used with View::scan_to
@@ -257,6 +674,76 @@ pub enum ScanToChoice {
     Abort(bool),    // ie. give up, stop there (bool = set marked?)
 }
 
+/// how many changes are kept for undo
+const HISTORY_LEN: usize = 50;
+/// and at most how many states (nodes) changed across all of them
+const HISTORY_WEIGHT: usize = 100_000;
+
+/// what the view history keeps of a state
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Flags {
+    unfolded: bool,
+    marked: bool,
+    flat: bool,
+}
+
+/// the view as seen by `history_checkpoint`; states are found by path
+/// (names joined with '/'), so it does not depend on the tree
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    flags: Vec<(String, Flags)>,
+    cursor: String,
+    settings: ViewSettings,
+}
+
+/// a change to the view, both ways (before, after)
+#[derive(Debug, Clone)]
+struct Change {
+    flags: Vec<(String, Flags, Flags)>,
+    cursor: (String, String),
+    settings: Option<Box<(ViewSettings, ViewSettings)>>,
+}
+
+impl Change {
+    /// `None` if nothing changed (cursor moves alone do not count)
+    fn between(before: Checkpoint, after: Checkpoint) -> Option<Change> {
+        let settings = (before.settings != after.settings)
+            .then(|| Box::new((before.settings, after.settings)));
+        if settings.is_none() && before.flags == after.flags {
+            return None;
+        }
+        let mut was = before.flags.into_iter().collect::<HashMap<_, _>>();
+        let mut flags = Vec::new();
+        for (path, now) in after.flags {
+            let then = was.remove(&path).unwrap_or_default();
+            if then != now {
+                flags.push((path, then, now));
+            }
+        }
+        flags.extend(was.into_iter().map(|(path, then)| (path, then, Flags::default())));
+        if settings.is_none() && flags.is_empty() {
+            return None;
+        }
+        // (outer ones first, for the inner ones to be found)
+        flags.sort_by_key(|(path, _, _)| path.matches('/').count());
+        Some(Change {
+            flags,
+            cursor: (before.cursor, after.cursor),
+            settings,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    /// taken by `history_checkpoint` until `history_commit`
+    pending: Option<Checkpoint>,
+    /// set by undo/redo themselves, so they are not recorded
+    moved: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct Offset {
     pub shift: i32,  // horizontally
@@ -270,6 +757,14 @@ pub struct View {
     cursor_path_len: usize,
     offset: Offset,
     settings: ViewSettings,
+    /// named sets of marked nodes, paths relative to the root
+    #[serde(default)]
+    selections: HashMap<String, Vec<PathBuf>>,
+    /// when in visual mode, the cursor path where it started
+    #[serde(skip)]
+    visual: Option<Vec<usize>>,
+    #[serde(skip)]
+    history: Box<History>,
 }
 
 impl View {
@@ -287,8 +782,10 @@ impl View {
                 shift: 0,
                 scroll: 0,
             },
-            // selection: vec![],
             settings,
+            selections: HashMap::new(),
+            visual: None,
+            history: Box::default(),
         })
     }
 
@@ -301,20 +798,23 @@ impl View {
     }
 
     pub fn cursor_offset(&self) -> Offset {
-        let acc = self
-            .cursor_path()
-            .iter()
-            .fold((&self.root, 1), |(state, acc), idx| {
-                let r = 1
-                    + acc
-                    + state.children[0..*idx]
-                        .iter()
-                        .map(|(_, ch)| ch.visible_height())
-                        .sum::<usize>();
-                let s = &state.children[*idx].1;
-                (s, r)
-            })
-            .1;
+        let path = self.cursor_path();
+        let mut state = &self.root;
+        let mut acc = 1;
+        for (depth, idx) in path.iter().enumerate() {
+            if let (true, Some(flat)) = (state.unfolded, &state.flat) {
+                let rest = &path[depth..];
+                acc += 1 + flat.iter().position(|it| it == rest).unwrap_or(0);
+                break;
+            }
+            acc = 1
+                + acc
+                + state.children[0..*idx]
+                    .iter()
+                    .map(|(_, ch)| ch.visible_height())
+                    .sum::<usize>();
+            state = &state.children[*idx].1;
+        }
         let len = self.cursor_path_len as i32;
         Offset {
             shift: len * 4 - self.offset.shift,
@@ -326,7 +826,50 @@ impl View {
         self.cursor_path_len = 0;
     }
 
+    /// depth (along the cursor path) of the outermost flattened
+    /// directory the cursor is at or in, if any
+    fn flat_depth(&self) -> Option<usize> {
+        let mut state = &self.root;
+        for (depth, idx) in self.cursor_path().iter().enumerate() {
+            if state.unfolded && state.flat.is_some() {
+                return Some(depth);
+            }
+            state = &state.children[*idx].1;
+        }
+        if state.unfolded && state.flat.is_some() {
+            Some(self.cursor_path_len)
+        } else {
+            None
+        }
+    }
+
+    fn at_depth(&self, depth: usize) -> &State {
+        self.cursor[..depth]
+            .iter()
+            .fold(&self.root, |acc_state, in_state_idx| {
+                &acc_state.children[*in_state_idx].1
+            })
+    }
+
+    /// when in a flattened directory, moves within its flat listing;
+    /// `to` gets the current position and the count of entries
+    fn flat_move(&mut self, to: impl FnOnce(Option<usize>, usize) -> usize) -> bool {
+        let Some(depth) = self.flat_depth().filter(|d| *d < self.cursor_path_len) else { return false; };
+        let flat = self.at_depth(depth).flat.as_ref().unwrap();
+        let rest = &self.cursor[depth..self.cursor_path_len];
+        let at = to(flat.iter().position(|it| it == rest), flat.len());
+        if let Some(path) = flat.get(at).cloned() {
+            self.cursor.truncate(depth);
+            self.cursor.extend(path);
+            self.cursor_path_len = self.cursor.len();
+        }
+        true
+    }
+
     pub fn cursor_to_first(&mut self) {
+        if self.flat_move(|_, _| 0) {
+            return;
+        }
         if 0 != self.cursor_path_len {
             if let Some(idx) = self.cursor.get_mut(self.cursor_path_len - 1) {
                 *idx = 0;
@@ -336,6 +879,9 @@ impl View {
     }
 
     pub fn cursor_to_last(&mut self) {
+        if self.flat_move(|_, len| len.saturating_sub(1)) {
+            return;
+        }
         if let Some(par) = self.at_parent() {
             let len = par.children.len();
             if let Some(idx) = self.cursor.get_mut(self.cursor_path_len - 1) {
@@ -407,7 +953,22 @@ impl View {
         &'a mut self,
         tree: &'a mut Tree,
     ) -> (&'a mut State, &'a mut Node) {
-        self.cursor.iter().take(self.cursor_path_len).fold(
+        let depth = self.cursor_path_len;
+        self.at_depth_pair_mut(tree, depth)
+    }
+
+    /// the folder the cursor is in (or the root itself)
+    fn at_parent_pair_mut<'a>(&'a mut self, tree: &'a mut Tree) -> (&'a mut State, &'a mut Node) {
+        let depth = self.cursor_path_len.saturating_sub(1);
+        self.at_depth_pair_mut(tree, depth)
+    }
+
+    fn at_depth_pair_mut<'a>(
+        &'a mut self,
+        tree: &'a mut Tree,
+        depth: usize,
+    ) -> (&'a mut State, &'a mut Node) {
+        self.cursor.iter().take(depth).fold(
             (&mut self.root, &mut tree.root),
             |(acc_state, acc_node): (&mut State, &mut Node), in_state_idx| {
                 let (in_node_idx, next_state) = &mut acc_state.children[*in_state_idx];
@@ -448,8 +1009,129 @@ impl View {
         ret
     }
 
+    /// paths of the marked nodes, relative to the root
+    pub fn collect_marked_paths(&self, tree: &Tree) -> Vec<PathBuf> {
+        let mut ret = Vec::new();
+        self.root
+            .collect_marked_paths(&tree.root, &mut PathBuf::new(), &mut ret);
+        ret
+    }
+
+    /// replace every mark with the given paths (relative to the root),
+    /// @ret the number that could not be found
+    pub fn set_marked_paths(&mut self, tree: &mut Tree, paths: &[PathBuf]) -> usize {
+        self.root.set_marked_rec(false);
+        let f_u = self.settings.clone();
+        paths
+            .iter()
+            .filter(|path| match path_names(path) {
+                Ok(Some(names)) => match self.root.reach(&mut tree.root, &f_u, &names) {
+                    Some((st, _)) => {
+                        st.marked = true;
+                        false
+                    }
+                    None => true,
+                },
+                _ => true,
+            })
+            .count()
+    }
+
+    pub fn clear_marked(&mut self) {
+        self.root.set_marked_rec(false);
+    }
+
+    /// within the folder the cursor is in, or its whole (loaded) subtree
+    pub fn invert_marked(&mut self, recursive: bool) {
+        let depth = self.cursor_path_len.saturating_sub(1);
+        self.cursor[..depth]
+            .iter()
+            .fold(&mut self.root, |acc_state, in_state_idx| {
+                &mut acc_state.children[*in_state_idx].1
+            })
+            .invert_marked(recursive);
+    }
+
+    pub fn save_selection(&mut self, name: &str, paths: Vec<PathBuf>) {
+        self.selections.insert(name.to_string(), paths);
+    }
+
+    pub fn get_selection(&self, name: &str) -> Option<&Vec<PathBuf>> {
+        self.selections.get(name)
+    }
+
+    pub fn drop_selection(&mut self, name: &str) -> bool {
+        self.selections.remove(name).is_some()
+    }
+
+    pub fn list_selections(&self) -> &HashMap<String, Vec<PathBuf>> {
+        &self.selections
+    }
+
+    pub fn visual_start(&mut self) {
+        self.visual = Some(self.cursor_path().to_vec());
+    }
+
+    pub fn visual_cancel(&mut self) {
+        self.visual = None;
+    }
+
+    pub fn in_visual(&self) -> bool {
+        self.visual.is_some()
+    }
+
+    /// full paths of the nodes between the visual mode start and the
+    /// cursor (only the cursor if not within the same folder/listing)
+    pub fn visual_range(&self) -> Vec<Vec<usize>> {
+        let Some(anchor) = &self.visual else { return Vec::new(); };
+        let cursor = self.cursor_path();
+
+        if let Some(depth) = self.flat_depth().filter(|d| *d < cursor.len()) {
+            let flat = self.at_depth(depth).flat.as_ref().unwrap();
+            let prefix = &cursor[..depth];
+            let pos = |path: &[usize]| flat.iter().position(|it| it == &path[depth..]);
+            if anchor.starts_with(prefix) && depth < anchor.len() {
+                if let (Some(a), Some(c)) = (pos(anchor), pos(cursor)) {
+                    let (lo, hi) = if a < c { (a, c) } else { (c, a) };
+                    return flat[lo..=hi]
+                        .iter()
+                        .map(|it| [prefix, it].concat())
+                        .collect();
+                }
+            }
+        } else if let (Some((a, aprefix)), Some((c, cprefix))) =
+            (anchor.split_last(), cursor.split_last())
+        {
+            if aprefix == cprefix {
+                let (lo, hi) = if a < c { (*a, *c) } else { (*c, *a) };
+                return (lo..=hi).map(|k| [cprefix, &[k]].concat()).collect();
+            }
+        }
+
+        vec![cursor.to_vec()]
+    }
+
+    /// apply to every node in the visual range, then leave visual mode
+    pub fn visual_apply(&mut self, does: impl Fn(&mut State)) {
+        for path in self.visual_range() {
+            let state = path.iter().try_fold(&mut self.root, |acc_state, in_state_idx| {
+                acc_state.children.get_mut(*in_state_idx).map(|(_, st)| st)
+            });
+            if let Some(state) = state {
+                does(state);
+            }
+        }
+        self.visual = None;
+    }
+
     pub fn enter(&mut self) {
-        if !self.at_cursor().children.is_empty() {
+        if let (true, Some(flat)) = (self.unfolded(), &self.at_cursor().flat) {
+            if let Some(first) = flat.first().cloned() {
+                self.cursor.truncate(self.cursor_path_len);
+                self.cursor.extend(first);
+                self.cursor_path_len = self.cursor.len();
+            }
+        } else if !self.at_cursor().children.is_empty() {
             if self.cursor.len() == self.cursor_path_len {
                 self.cursor.push(0);
             }
@@ -458,12 +1140,17 @@ impl View {
     }
 
     pub fn leave(&mut self) {
-        if 0 < self.cursor_path_len {
+        if let Some(depth) = self.flat_depth().filter(|d| *d < self.cursor_path_len) {
+            self.cursor_path_len = depth;
+        } else if 0 < self.cursor_path_len {
             self.cursor_path_len -= 1;
         }
     }
 
     pub fn next(&mut self) {
+        if self.flat_move(|at, len| at.map_or(0, |at| if at + 1 < len { at + 1 } else { at })) {
+            return;
+        }
         if let Some(par) = self.at_parent() {
             let len = par.children.len();
             if let Some(idx) = self.cursor.get_mut(self.cursor_path_len - 1) {
@@ -476,6 +1163,9 @@ impl View {
     }
 
     pub fn prev(&mut self) {
+        if self.flat_move(|at, _| at.map_or(0, |at| at.saturating_sub(1))) {
+            return;
+        }
         if 0 != self.cursor_path_len {
             if let Some(idx) = self.cursor.get_mut(self.cursor_path_len - 1) {
                 if 0 < *idx {
@@ -528,10 +1218,134 @@ impl View {
         }
     }
 
+    /// names along the cursor path, joined with '/'
+    fn cursor_names(&self, tree: &Tree) -> String {
+        let mut names = Vec::new();
+        let (mut st, mut nd) = (&self.root, &tree.root);
+        for idx in self.cursor_path() {
+            let (k, next) = &st.children[*idx];
+            nd = &nd.loaded_children().unwrap()[*k];
+            st = next;
+            names.push(nd.file_name());
+        }
+        names.join("/")
+    }
+
+    /// put the cursor on the node at `names` (joined with '/'),
+    /// or as close as it can be while still visible
+    fn cursor_to_names(&mut self, tree: &Tree, names: &str) {
+        let mut cursor = Vec::new();
+        let (mut st, mut nd) = (&self.root, &tree.root);
+        for name in names.split('/').filter(|it| !it.is_empty()) {
+            let Some(chs) = nd.loaded_children().filter(|_| st.unfolded) else { break; };
+            let Some(idx) = st
+                .children
+                .iter()
+                .position(|(k, _)| chs[*k].file_name() == name)
+            else {
+                break;
+            };
+            let (k, next) = &st.children[idx];
+            cursor.push(idx);
+            st = next;
+            nd = &chs[*k];
+        }
+        self.cursor = cursor;
+        self.cursor_path_len = self.cursor.len();
+    }
+
+    fn checkpoint(&self, tree: &Tree) -> Checkpoint {
+        let mut flags = Vec::new();
+        self.root
+            .collect_flags(&tree.root, &mut String::new(), &mut flags);
+        Checkpoint {
+            flags,
+            cursor: self.cursor_names(tree),
+            settings: self.settings.clone(),
+        }
+    }
+
+    /// bring the view to one side of `change` (the before if `back`)
+    fn apply(&mut self, tree: &mut Tree, change: &Change, back: bool) {
+        if let Some(settings) = &change.settings {
+            let (before, after) = &**settings;
+            self.settings = if back { before } else { after }.clone();
+            // (listed again with the same tree: sorted and filtered anew)
+            self.cursor.truncate(self.cursor_path_len);
+            self.root = self
+                .root
+                .renew(&tree.root, &tree.root, &self.settings, &mut self.cursor, 0);
+        }
+        let settings = self.settings.clone();
+        for (path, before, after) in &change.flags {
+            let names = path.split('/').filter(|it| !it.is_empty()).collect::<Vec<_>>();
+            let Some((st, node)) = self.root.reach(&mut tree.root, &settings, &names) else {
+                continue;
+            };
+            let flags = if back { before } else { after };
+            st.marked = flags.marked;
+            st.flat = flags.flat.then(|| st.flat.take().unwrap_or_default());
+            if flags.unfolded {
+                // (what cannot be read anymore stays folded)
+                let _ = st.unfold(node, &settings);
+            } else {
+                st.fold();
+            }
+        }
+        self.root.refresh_flat(&mut tree.root, &settings);
+        self.visual = None;
+        let (before, after) = &change.cursor;
+        self.cursor_to_names(tree, if back { before } else { after });
+        self.history.moved = true;
+    }
+
+    /// to be followed by `history_commit` after some changes
+    pub fn history_checkpoint(&mut self, tree: &Tree) {
+        self.history.pending = Some(self.checkpoint(tree));
+        self.history.moved = false;
+    }
+
+    /// record what changed since the checkpoint in the undo history,
+    /// if the folding, marks, filters or sorting did (cursor moves
+    /// alone do not)
+    pub fn history_commit(&mut self, tree: &Tree) {
+        let Some(before) = self.history.pending.take() else { return; };
+        if std::mem::take(&mut self.history.moved) {
+            return;
+        }
+        let Some(change) = Change::between(before, self.checkpoint(tree)) else { return; };
+        self.history.undo.push_back(change);
+        self.history.redo.clear();
+
+        let mut weight = self.history.undo.iter().map(|it| it.flags.len()).sum::<usize>();
+        while HISTORY_LEN < self.history.undo.len() || HISTORY_WEIGHT < weight {
+            let Some(oldest) = self.history.undo.pop_front() else { break; };
+            weight -= oldest.flags.len();
+        }
+    }
+
+    /// @ret false if there was nothing to undo
+    pub fn undo(&mut self, tree: &mut Tree) -> bool {
+        let Some(change) = self.history.undo.pop_back() else { return false; };
+        self.apply(tree, &change, true);
+        self.history.redo.push(change);
+        true
+    }
+
+    pub fn redo(&mut self, tree: &mut Tree) -> bool {
+        let Some(change) = self.history.redo.pop() else { return false; };
+        self.apply(tree, &change, false);
+        self.history.undo.push_back(change);
+        true
+    }
+
     /// re-create the view against the given tree
     /// while trying to keep the same state (eg.
     /// unfolded, selected, cursor...)
     pub fn fixup(&mut self, ptree: &Tree, tree: &Tree) {
+        // the visual anchor is not kept up to date
+        self.visual = None;
+
         // XXX/TODO: could also update past-len idxs;
         // for that will need an inout_path_len, won't be
         // able to rely on inout_path.len() anymore
@@ -542,6 +1356,50 @@ impl View {
         self.cursor_path_len = self.cursor.len();
     }
 
+    /// same as `fixup` but after `Tree::renew_within`, only
+    /// following what changed
+    pub fn fixup_within(&mut self, tree: &Tree, renewed: &[Renewed]) {
+        let mut renewed = renewed
+            .iter()
+            .filter_map(|it| {
+                let rel = it.path().strip_prefix(tree.root.as_path()).ok()?;
+                Some((path_names(rel).ok()??, it))
+            })
+            .collect::<Vec<_>>();
+        // (outer listings first, for the inner names to be found)
+        renewed.sort_by_key(|(names, _)| names.len());
+
+        self.visual = None;
+        self.cursor.truncate(self.cursor_path_len);
+        for (names, it) in &renewed {
+            self.root
+                .follow_renewed(&tree.root, names, it, &self.settings, &mut self.cursor, 0);
+        }
+        self.cursor_path_len = self.cursor.len();
+    }
+
+    /// unfold the sub-directories that appeared in flattened
+    /// directories (after a `fixup`) and list them again
+    pub fn refresh_flat(&mut self, tree: &mut Tree) {
+        let f_u = self.settings.clone();
+        self.root.refresh_flat(&mut tree.root, &f_u);
+    }
+
+    /// unfold every ancestor of the node at `path` (relative to
+    /// the root) and move the cursor onto it; false if not found
+    pub fn reveal(&mut self, tree: &mut Tree, path: &Path) -> io::Result<bool> {
+        let Some(names) = path_names(path)? else { return Ok(false); };
+        let settings = self.settings.clone();
+        let mut path = Vec::new();
+        if self.root.reveal(&mut tree.root, &settings, &names, &mut path)? {
+            self.cursor = path;
+            self.cursor_path_len = self.cursor.len();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     pub fn unfold_root(&mut self, tree: &mut Tree) -> io::Result<()> {
         self.root.unfold(&mut tree.root, &self.settings)
     }
@@ -556,6 +1414,97 @@ impl View {
         state.unfold(node, &f_u)
     }
 
+    /// unfold the node at the cursor and its sub-directories, up to
+    /// `depth` levels (1 is the same as `unfold`); @ret the errors,
+    /// if any
+    pub fn unfold_rec(&mut self, tree: &mut Tree, depth: usize) -> Vec<String> {
+        let f_u = self.settings.clone();
+        let (state, node) = self.at_cursor_pair_mut(tree);
+        let mut ancestors = vec![identity(node)];
+        let mut errors = Vec::new();
+        state.unfold_rec(node, &f_u, depth, &mut ancestors, &mut errors);
+        errors
+    }
+
+    pub fn fold_rec(&mut self) {
+        self.at_cursor_mut().fold_rec();
+    }
+
+    /// mark the nodes matching `predicate` in the folder the cursor is
+    /// in, or in its whole subtree if `recursive` (ancestors of the
+    /// marked nodes are unfolded), @ret the number of nodes marked
+    /// and the errors, if any
+    pub fn mark_matching(
+        &mut self,
+        tree: &mut Tree,
+        recursive: bool,
+        predicate: &impl Fn(&Node) -> bool,
+    ) -> (usize, Vec<String>) {
+        let f_u = self.settings.clone();
+        let (state, node) = self.at_parent_pair_mut(tree);
+        let mut ancestors = vec![identity(node)];
+        let depth = if recursive { usize::MAX } else { 1 };
+        let mut errors = Vec::new();
+        let count = state.mark_matching(node, &f_u, depth, predicate, &mut ancestors, &mut errors);
+        (count, errors)
+    }
+
+    /// fold everything but the root, the cursor goes to the top level
+    pub fn fold_all(&mut self) {
+        for (_, ch) in &mut self.root.children {
+            ch.fold_rec();
+        }
+        if 1 < self.cursor_path_len {
+            self.cursor_path_len = 1;
+            self.cursor.truncate(1);
+        }
+    }
+
+    /// fold everything that is not on the path to the cursor
+    pub fn fold_others(&mut self) {
+        let mut state = &mut self.root;
+        for idx in &self.cursor[..self.cursor_path_len] {
+            for (k, (_, ch)) in state.children.iter_mut().enumerate() {
+                if k != *idx {
+                    ch.fold_rec();
+                }
+            }
+            state = &mut state.children[*idx].1;
+        }
+        for (_, ch) in &mut state.children {
+            ch.fold_rec();
+        }
+    }
+
+    /// toggle a flat listing of the directory at the cursor (or the
+    /// one the cursor is in), it is recursively unfolded first
+    pub fn toggle_flatten(&mut self, tree: &mut Tree) -> io::Result<()> {
+        if let Some(depth) = self.flat_depth() {
+            // cursor path stays valid as the nested states are kept
+            let path = self.cursor[..depth].to_vec();
+            path.iter()
+                .fold(&mut self.root, |acc_state, in_state_idx| {
+                    &mut acc_state.children[*in_state_idx].1
+                })
+                .flat = None;
+            return Ok(());
+        }
+
+        // on a file, flatten its parent and stay on it
+        let was_len = self.cursor_path_len;
+        let (_, node) = self.at_cursor_pair(tree);
+        if !node.is_dir_through() {
+            self.leave();
+        }
+
+        let f_u = self.settings.clone();
+        let (state, node) = self.at_cursor_pair_mut(tree);
+        state.flat = Some(Vec::new());
+        state.unfold(node, &f_u)?;
+        self.cursor_path_len = was_len;
+        Ok(())
+    }
+
     pub fn unfolded(&self) -> bool {
         self.at_cursor().unfolded
     }
@@ -581,7 +1530,7 @@ impl View {
         (self.settings.sort, self.settings.reverse)
     }
     pub fn add_filtering(&mut self, filter: Filtering) {
-        if !self.settings.filters.iter().any(|it| *it == filter) {
+        if !self.settings.filters.contains(&filter) {
             self.settings.filters.push(filter);
         }
     }